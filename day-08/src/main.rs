@@ -1,5 +1,5 @@
 use itertools::Itertools;
-use shared::{Coordinate, Displacement, Maze};
+use shared::{geometry, Coordinate, Displacement, Maze};
 use std::collections::HashSet;
 
 fn main() {
@@ -55,21 +55,17 @@ fn part2(data: &ParsedData) -> usize {
     let mut antinodes: HashSet<Coordinate> = HashSet::new();
     for &frequency in frequencies {
         let antennae = maze.find_all(frequency);
-        antennae.iter().permutations(2).for_each(|permutation| {
-            let [&a, &b] = permutation[..] else {
-                panic!("Unable to parse permutation");
-            };
-            let delta: Displacement = b - a;
-            let mut antinode = a;
-            loop {
-                if maze.contains_coordinate(antinode) {
+        for (&a, &b) in antennae.iter().tuple_combinations() {
+            // Every grid point on the line through both antennae, including those between them
+            let delta: Displacement = geometry::primitive(b - a);
+            for step in [delta, -delta] {
+                let mut antinode = a;
+                while maze.contains_coordinate(antinode) {
                     antinodes.insert(antinode);
-                    antinode -= delta;
-                    continue;
+                    antinode += step;
                 }
-                break;
             }
-        });
+        }
     }
 
     antinodes.len()
@@ -107,3 +103,21 @@ mod integration {
         assert_eq!(value, expected);
     }
 }
+
+#[cfg(test)]
+mod unit {
+    #[test]
+    fn antinodes_between_antennae() {
+        const INPUT: &str = r"a....
+.....
+.....
+.....
+..a..
+";
+        // The antennae are (2, 4) apart, so (1, 2) lies on the line between them
+        let parsed = crate::parse(INPUT);
+        let value = crate::part2(&parsed);
+        let expected = 3;
+        assert_eq!(value, expected);
+    }
+}
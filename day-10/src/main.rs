@@ -1,10 +1,8 @@
 use std::collections::HashSet;
 
-use itertools::Itertools;
-
 use shared::{
-    Coordinate,
-    Direction::{E, N, S, W},
+    pathfinding, Coordinate,
+    Direction::{self, E, N, S, W},
     Maze, Visitor,
 };
 
@@ -64,16 +62,30 @@ fn part1(data: &ParsedData) -> usize {
     score
 }
 
+fn height(maze: &Maze, coordinate: Coordinate) -> Option<u32> {
+    maze.get(coordinate)?.to_digit(10)
+}
+
 fn part2(data: &ParsedData) -> usize {
-    let maze = data.clone();
+    let maze = data;
     let trailheads = maze.find_all('0');
     let mut paths = 0;
-    let maze = maze.make_shareable();
     for coordinate in trailheads {
-        let mut summits: HashSet<Coordinate> = HashSet::new();
-        let visitor = Visitor::new(&maze.clone(), coordinate);
-        let climbers = climb(visitor, &mut summits);
-        paths += climbers.iter().map(Visitor::path).unique().count();
+        // Every trail is exactly nine steps long, so all trails are shortest paths to their summits
+        let trails = pathfinding::dijkstra_all(
+            coordinate,
+            |&c| {
+                let next = height(maze, c).map(|h| h + 1);
+                Direction::cardinals()
+                    .map(move |d| c + d.to_displacement())
+                    .filter(move |&n| next.is_some() && height(maze, n) == next)
+                    .map(|n| (n, 1))
+            },
+            |&c| height(maze, c) == Some(9),
+        );
+        if let Some(trails) = trails {
+            paths += usize::try_from(trails.count_paths()).expect("Too many trails");
+        }
     }
 
     paths
@@ -1,5 +1,6 @@
 pub mod robot;
 use robot::{looks_like_a_tree, safety_score, Robot, BATHROOM_HEIGHT, BATHROOM_WIDTH};
+use shared::{Order, Scheduler};
 
 fn main() {
     const INPUT: &str = include_str!("input.txt");
@@ -23,22 +24,19 @@ fn parse(input: &str) -> ParsedData {
 
 fn part1(data: &ParsedData) -> usize {
     let mut robots = data.clone();
-    robots.iter_mut().for_each(|r| r.step_n(100));
+    for robot in &mut robots {
+        robot.step_n(100);
+    }
     safety_score(&robots)
 }
 
 fn part2(data: &ParsedData) -> usize {
-    let mut robots = data.clone();
-    for step in 0..(BATHROOM_WIDTH * BATHROOM_HEIGHT) {
-        if looks_like_a_tree(&robots) {
-            // robot::print_bathroom(&robots);
-            #[allow(clippy::cast_sign_loss)]
-            return step as usize;
-        };
-        robots.iter_mut().for_each(Robot::step);
-    }
-
-    0
+    let mut scheduler = Scheduler::without_maze(data.clone()).with_order(Order::Simultaneous);
+    #[allow(clippy::cast_sign_loss)]
+    let max_steps = (BATHROOM_WIDTH * BATHROOM_HEIGHT) as usize;
+    scheduler
+        .run_until(max_steps, |_bathroom, robots| looks_like_a_tree(robots))
+        .unwrap_or(0)
 }
 
 #[cfg(test)]
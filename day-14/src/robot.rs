@@ -1,5 +1,5 @@
 use itertools::Itertools;
-use shared::{Coordinate, Displacement, Maze};
+use shared::{Agent, Coordinate, Displacement, Maze};
 
 #[cfg(test)]
 pub(crate) const BATHROOM_WIDTH: isize = 11;
@@ -18,8 +18,15 @@ pub struct Robot {
 
 impl Robot {
     pub fn step(&mut self) {
-        self.position.x = (self.position.x + self.velocity.x).rem_euclid(BATHROOM_WIDTH);
-        self.position.y = (self.position.y + self.velocity.y).rem_euclid(BATHROOM_HEIGHT);
+        self.position = self.next_position();
+    }
+
+    #[must_use]
+    pub fn next_position(&self) -> Coordinate {
+        Coordinate::new(
+            (self.position.x + self.velocity.x).rem_euclid(BATHROOM_WIDTH),
+            (self.position.y + self.velocity.y).rem_euclid(BATHROOM_HEIGHT),
+        )
     }
 
     pub fn step_n(&mut self, n: isize) {
@@ -28,6 +35,20 @@ impl Robot {
     }
 }
 
+impl Agent for Robot {
+    fn position(&self) -> Coordinate {
+        self.position
+    }
+
+    fn intent(&mut self, _bathroom: &Maze) -> Option<Coordinate> {
+        Some(self.next_position())
+    }
+
+    fn advance(&mut self, coordinate: Coordinate) {
+        self.position = coordinate;
+    }
+}
+
 impl TryFrom<&str> for Robot {
     type Error = String;
 
@@ -67,7 +88,7 @@ pub fn safety_score(robots: &Vec<Robot>) -> usize {
             (std::cmp::Ordering::Greater, std::cmp::Ordering::Less) => quads[1] += 1,
             (std::cmp::Ordering::Less, std::cmp::Ordering::Greater) => quads[2] += 1,
             (std::cmp::Ordering::Greater, std::cmp::Ordering::Greater) => quads[3] += 1,
-            _ => continue,
+            _ => (),
         }
     }
 
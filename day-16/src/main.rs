@@ -1,3 +1,11 @@
+use std::collections::HashSet;
+
+use shared::{
+    geometry,
+    pathfinding::{self, Path, ShortestPaths},
+    Coordinate, Direction, Maze,
+};
+
 fn main() {
     const INPUT: &str = include_str!("input.txt");
     let parsed = parse(INPUT);
@@ -9,29 +17,90 @@ fn main() {
     println!("Part 2: {value}");
 }
 
-type ParsedData = Vec<()>;
+type ParsedData = Maze;
+
+fn parse(input: &str) -> ParsedData {
+    input.parse().expect("Unable to parse maze")
+}
+
+const STEP_COST: usize = 1;
+const TURN_COST: usize = 1000;
+
+/// A reindeer is described by its position and the direction it is facing.
+type Reindeer = (Coordinate, Direction);
+
+fn moves(maze: &Maze, &(coordinate, heading): &Reindeer) -> Vec<(Reindeer, usize)> {
+    let mut moves = vec![
+        ((coordinate, heading.rotate_cw()), TURN_COST),
+        ((coordinate, heading.rotate_ccw()), TURN_COST),
+    ];
+    let forward = coordinate + heading.to_displacement();
+    if maze.get(forward).is_some_and(|c| c != '#') {
+        moves.push(((forward, heading), STEP_COST));
+    }
+    moves
+}
 
-fn parse(_input: &str) -> ParsedData {
-    Vec::new()
+fn best_path(maze: &Maze) -> Option<Path<Reindeer>> {
+    let start = maze.find('S')?;
+    let end = maze.find('E')?;
+    pathfinding::astar(
+        (start, Direction::E),
+        |reindeer| moves(maze, reindeer),
+        |&(coordinate, _)| geometry::manhattan(coordinate, end),
+        |&(coordinate, _)| coordinate == end,
+    )
 }
 
-fn part1(_data: &ParsedData) -> usize {
-    0
+fn best_paths(maze: &Maze) -> Option<ShortestPaths<Reindeer>> {
+    let start = maze.find('S')?;
+    let end = maze.find('E')?;
+    pathfinding::dijkstra_all(
+        (start, Direction::E),
+        |reindeer| moves(maze, reindeer),
+        |&(coordinate, _)| coordinate == end,
+    )
 }
 
-fn part2(_data: &ParsedData) -> usize {
-    0
+fn part1(data: &ParsedData) -> usize {
+    best_path(data).map_or(0, |path| path.cost)
+}
+
+fn part2(data: &ParsedData) -> usize {
+    best_paths(data).map_or(0, |paths| {
+        let tiles: HashSet<Coordinate> = paths
+            .states()
+            .into_iter()
+            .map(|(coordinate, _)| coordinate)
+            .collect();
+        tiles.len()
+    })
 }
 
 #[cfg(test)]
 mod integration {
-    const INPUT: &str = r"";
+    const INPUT: &str = r"###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+";
 
     #[test]
     fn part1() {
         let parsed = crate::parse(INPUT);
         let value = crate::part1(&parsed);
-        let expected = 0;
+        let expected = 7036;
         assert_eq!(value, expected);
     }
 
@@ -39,17 +108,41 @@ mod integration {
     fn part2() {
         let parsed = crate::parse(INPUT);
         let value = crate::part2(&parsed);
-        let expected = 0;
+        let expected = 45;
         assert_eq!(value, expected);
     }
 }
 
 #[cfg(test)]
 mod unit {
+    const INPUT: &str = r"#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################
+";
+
     #[test]
-    fn unit() {
-        let value = 0;
-        let expected = 0;
+    fn second_example() {
+        let parsed = crate::parse(INPUT);
+        let value = crate::part1(&parsed);
+        let expected = 11048;
+        assert_eq!(value, expected);
+
+        let value = crate::part2(&parsed);
+        let expected = 64;
         assert_eq!(value, expected);
     }
 }
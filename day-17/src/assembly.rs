@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use crate::computer::{Instruction, Operand, Program};
+
+#[allow(clippy::enum_glob_use)]
+use Instruction::*;
+#[allow(clippy::enum_glob_use)]
+use Operand::*;
+
+const INSTRUCTIONS: [Instruction; 8] = [adv, bxl, bst, jnz, bxc, out, bdv, cdv];
+const OPERANDS: [Operand; 8] = [op0, op1, op2, op3, op4, op5, op6, op7];
+
+/// Renders the operand of the instruction as it is read: combo operands as the register
+/// name or literal value, and literal operands as their value.
+#[must_use]
+pub fn operand_name(instruction: Instruction, operand: Operand) -> String {
+    if !instruction.takes_combo() {
+        return operand.to_string();
+    }
+    match operand as u8 {
+        4 => "a".to_string(),
+        5 => "b".to_string(),
+        6 => "c".to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Describes what the instruction does, in terms of the registers it reads and writes.
+#[must_use]
+pub fn describe(instruction: Instruction, operand: Operand) -> String {
+    if instruction.takes_combo() && operand as u8 == 7 {
+        return "reserved combo operand 7".to_string();
+    }
+    let x = operand_name(instruction, operand);
+    match instruction {
+        adv => format!("a = a >> {x}"),
+        bxl => format!("b = b ^ {x}"),
+        bst => format!("b = {x} & 7"),
+        jnz => format!("jump to {x} if a != 0"),
+        bxc => "b = b ^ c".to_string(),
+        out => format!("output {x} & 7"),
+        bdv => format!("b = a >> {x}"),
+        cdv => format!("c = a >> {x}"),
+    }
+}
+
+/// Renders a single instruction at the given address as a line of annotated assembly, without a line break.
+#[must_use]
+pub fn disassemble_instruction(
+    address: usize,
+    instruction: Instruction,
+    operand: Operand,
+) -> String {
+    format!(
+        "{address:02}: {instruction} {:<2} ; {}",
+        operand_name(instruction, operand),
+        describe(instruction, operand)
+    )
+}
+
+/// Renders the program as annotated assembly, one instruction per line, prefixed with its address.
+///
+/// Addresses count the numbers of the program, like the instruction pointer and jump targets do,
+/// so each instruction is two addresses after the previous one.
+#[must_use]
+pub fn disassemble(program: &Program) -> String {
+    let mut assembly = String::new();
+    for (index, &(instruction, operand)) in program.iter().enumerate() {
+        assembly.push_str(&disassemble_instruction(index * 2, instruction, operand));
+        assembly.push('\n');
+    }
+    assembly
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Line {line}: {kind}")]
+pub struct AssemblyError {
+    /// Line of the source the error was found on, starting from 1
+    pub line: usize,
+    pub kind: AssemblyErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum AssemblyErrorKind {
+    #[error("Unknown instruction `{0}`")]
+    UnknownInstruction(String),
+    #[error("Missing operand")]
+    MissingOperand,
+    #[error("Unexpected `{0}` after the operand")]
+    TrailingInput(String),
+    #[error("Invalid operand `{0}`")]
+    InvalidOperand(String),
+    #[error("Combo operand 7 is reserved")]
+    ReservedOperand,
+    #[error("Combo operand {0} reads a register, write it as `{1}`")]
+    NumericRegister(u8, char),
+    #[error("`{0}` takes a literal operand, not register `{1}`")]
+    RegisterOperand(Instruction, char),
+    #[error("Invalid label `{0}`")]
+    InvalidLabel(String),
+    #[error("Label `{0}` is already defined")]
+    DuplicateLabel(String),
+    #[error("Undefined label `{0}`")]
+    UndefinedLabel(String),
+    #[error("Label `{0}` is at address {1}, beyond the jump range 0..=7")]
+    LabelOutOfRange(String, usize),
+}
+
+/// An instruction whose operand has not been resolved yet, as labels may be defined after their use.
+struct Line<'a> {
+    number: usize,
+    instruction: Instruction,
+    operand: Option<&'a str>,
+}
+
+/// Assembles mnemonic source into a [`Program`].
+///
+/// Each line holds at most one instruction, such as `adv 3`, `bxl 5` or `out b`, and anything after a `;`
+/// is a comment. Combo operands are written as `0` to `3` or a register name `a`, `b` or `c`.
+/// A line may start with a label like `loop:`, which can then be used as the operand of `jnz`.
+/// Numeric prefixes like `04:` are ignored, so the output of [`disassemble`] assembles back to the same program.
+///
+/// # Errors
+///
+/// Returns the first error with its line number, if an instruction, operand or label is invalid.
+pub fn assemble(source: &str) -> Result<Program, AssemblyError> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut lines: Vec<Line> = Vec::new();
+    for (index, text) in source.lines().enumerate() {
+        let number = index + 1;
+        let error = |kind| AssemblyError { line: number, kind };
+        let mut text = text.split(';').next().unwrap_or_default().trim();
+        while let Some((prefix, rest)) = text.split_once(':') {
+            let prefix = prefix.trim();
+            if !prefix.chars().all(|c| c.is_ascii_digit()) {
+                if !is_label(prefix) {
+                    return Err(error(AssemblyErrorKind::InvalidLabel(prefix.to_string())));
+                }
+                if labels.insert(prefix, lines.len() * 2).is_some() {
+                    return Err(error(AssemblyErrorKind::DuplicateLabel(prefix.to_string())));
+                }
+            }
+            text = rest.trim();
+        }
+
+        let mut words = text.split_whitespace();
+        let Some(mnemonic) = words.next() else {
+            continue;
+        };
+        let instruction = INSTRUCTIONS
+            .into_iter()
+            .find(|i| i.to_string() == mnemonic)
+            .ok_or_else(|| error(AssemblyErrorKind::UnknownInstruction(mnemonic.to_string())))?;
+        let operand = words.next();
+        if let Some(extra) = words.next() {
+            return Err(error(AssemblyErrorKind::TrailingInput(extra.to_string())));
+        }
+        lines.push(Line {
+            number,
+            instruction,
+            operand,
+        });
+    }
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let operand = resolve(&line, &labels).map_err(|kind| AssemblyError {
+                line: line.number,
+                kind,
+            })?;
+            Ok((line.instruction, operand))
+        })
+        .collect()
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn resolve(line: &Line, labels: &HashMap<&str, usize>) -> Result<Operand, AssemblyErrorKind> {
+    let instruction = line.instruction;
+    let Some(operand) = line.operand else {
+        // `bxc` ignores its operand, so it may be left out
+        return match instruction {
+            bxc => Ok(op0),
+            _ => Err(AssemblyErrorKind::MissingOperand),
+        };
+    };
+    let register = match operand {
+        "a" => Some(('a', op4)),
+        "b" => Some(('b', op5)),
+        "c" => Some(('c', op6)),
+        _ => None,
+    };
+    if let Some((name, value)) = register {
+        return match instruction.takes_combo() {
+            true => Ok(value),
+            false => Err(AssemblyErrorKind::RegisterOperand(instruction, name)),
+        };
+    }
+    if let Ok(value) = operand.parse::<u8>() {
+        return match (instruction.takes_combo(), value) {
+            (_, 8..) => Err(AssemblyErrorKind::InvalidOperand(operand.to_string())),
+            (true, 7) => Err(AssemblyErrorKind::ReservedOperand),
+            (true, 4..=6) => Err(AssemblyErrorKind::NumericRegister(
+                value,
+                char::from(b'a' + value - 4),
+            )),
+            _ => Ok(OPERANDS[usize::from(value)]),
+        };
+    }
+    match (instruction, labels.get(operand)) {
+        (jnz, Some(&address)) => OPERANDS
+            .get(address)
+            .copied()
+            .ok_or_else(|| AssemblyErrorKind::LabelOutOfRange(operand.to_string(), address)),
+        (jnz, None) if is_label(operand) => {
+            Err(AssemblyErrorKind::UndefinedLabel(operand.to_string()))
+        }
+        _ => Err(AssemblyErrorKind::InvalidOperand(operand.to_string())),
+    }
+}
+
+/// Renders the program in the comma-separated numeric form of the puzzle input.
+#[must_use]
+pub fn to_numeric(program: &Program) -> String {
+    program
+        .iter()
+        .map(|&(instruction, operand)| format!("{},{}", instruction as u8, operand as u8))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::computer::Computer;
+
+    #[test]
+    fn disassemble() {
+        let computer =
+            Computer::new(0, 0, 0, "2,4,1,2,7,5,4,1,5,5,3,0,6,7").expect("Unable to load program");
+        let expected = "\
+00: bst a  ; b = a & 7
+02: bxl 2  ; b = b ^ 2
+04: cdv b  ; c = a >> b
+06: bxc 1  ; b = b ^ c
+08: out b  ; output b & 7
+10: jnz 0  ; jump to 0 if a != 0
+12: bdv 7  ; reserved combo operand 7
+";
+        assert_eq!(computer.disassemble(), expected);
+        assert_eq!(computer.print_program(), "2,4,1,2,7,5,4,1,5,5,3,0,6,7");
+    }
+
+    #[test]
+    fn assemble() {
+        let source = "\
+; Prints the lowest three bits of a at a time
+loop:
+    adv 1       ; Halve a
+    out a
+    jnz loop
+";
+        let program = super::assemble(source).expect("Unable to assemble");
+        assert_eq!(to_numeric(&program), "0,1,5,4,3,0");
+        let mut computer = Computer::default();
+        computer.load_program(program);
+        computer.set_register('a', 729);
+        computer.run(false).expect("Program failed");
+        assert_eq!(computer.print(), "4,6,3,5,6,3,5,2,1,0");
+
+        // Disassembly assembles back to the same program
+        let computer =
+            Computer::new(0, 0, 0, "2,4,1,2,7,5,4,1,5,5,3,0").expect("Unable to load program");
+        let program = super::assemble(&computer.disassemble()).expect("Unable to assemble");
+        assert_eq!(&program, computer.program());
+    }
+
+    #[test]
+    fn assembly_errors() {
+        let error = |source: &str| {
+            let error = super::assemble(source).expect_err("Assembled invalid source");
+            (error.line, error.kind)
+        };
+        assert_eq!(
+            error("adv 1\nout 7"),
+            (2, AssemblyErrorKind::ReservedOperand)
+        );
+        assert_eq!(
+            error("bxl a"),
+            (1, AssemblyErrorKind::RegisterOperand(Instruction::bxl, 'a'))
+        );
+        assert_eq!(
+            error("bst 4"),
+            (1, AssemblyErrorKind::NumericRegister(4, 'a'))
+        );
+        assert_eq!(
+            error("\n\nmul 2"),
+            (3, AssemblyErrorKind::UnknownInstruction("mul".to_string()))
+        );
+        assert_eq!(
+            error("jnz end\nadv 1"),
+            (1, AssemblyErrorKind::UndefinedLabel("end".to_string()))
+        );
+        assert_eq!(
+            error("bxl 1\nbxl 1\nbxl 1\nbxl 1\nend: jnz end"),
+            (5, AssemblyErrorKind::LabelOutOfRange("end".to_string(), 8))
+        );
+        assert_eq!(
+            error("x: adv 1\nx: adv 1"),
+            (2, AssemblyErrorKind::DuplicateLabel("x".to_string()))
+        );
+        assert_eq!(error("out"), (1, AssemblyErrorKind::MissingOperand));
+        assert_eq!(
+            error("bxl 9"),
+            (1, AssemblyErrorKind::InvalidOperand("9".to_string()))
+        );
+        assert_eq!(
+            error("adv 1 2"),
+            (1, AssemblyErrorKind::TrailingInput("2".to_string()))
+        );
+        assert_eq!(
+            super::assemble("bxc").map(|p| to_numeric(&p)),
+            Ok("4,0".to_string())
+        );
+    }
+}
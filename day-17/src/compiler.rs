@@ -0,0 +1,248 @@
+use crate::{
+    computer::{shift, Computer, Instruction, Operand, Program},
+    execution::ExecutionError,
+};
+
+#[allow(clippy::enum_glob_use)]
+use Instruction::*;
+#[allow(clippy::enum_glob_use)]
+use Operand::*;
+
+/// Registers and output of a running compiled program.
+struct Machine {
+    a: u64,
+    b: u64,
+    c: u64,
+    out: Vec<u8>,
+}
+
+/// What to execute after an operation.
+enum Flow {
+    Next,
+    /// Continue at the operation with this index
+    Jump(usize),
+    /// The operation reads the reserved combo operand `7`
+    Reserved,
+}
+
+type Operation = Box<dyn Fn(&mut Machine) -> Flow + Send + Sync>;
+
+/// Builds the operation for a combo instruction, with the register read or the constant value resolved
+/// once here instead of on every execution. `$x` is bound to the operand's value within `$body`.
+macro_rules! with_combo {
+    ($operand:expr, |$m:ident, $x:ident| $body:expr) => {
+        match $operand {
+            op4 => Box::new(|$m: &mut Machine| {
+                let $x = $m.a;
+                $body
+            }) as Operation,
+            op5 => Box::new(|$m: &mut Machine| {
+                let $x = $m.b;
+                $body
+            }),
+            op6 => Box::new(|$m: &mut Machine| {
+                let $x = $m.c;
+                $body
+            }),
+            op7 => Box::new(|_: &mut Machine| Flow::Reserved),
+            constant => {
+                let $x = Computer::literal(constant);
+                Box::new(move |$m: &mut Machine| $body)
+            }
+        }
+    };
+}
+
+/// A program translated into a chain of closures, one per instruction, for running it many times quickly.
+///
+/// Every operand is decoded when compiling, so running does no matching on instructions or operands.
+/// The results are identical to running the program on a [`Computer`].
+pub struct CompiledProgram {
+    operations: Vec<Operation>,
+}
+
+impl CompiledProgram {
+    #[must_use]
+    pub fn new(program: &Program) -> Self {
+        let operations = program
+            .iter()
+            .map(|&(instruction, operand)| compile(instruction, operand))
+            .collect();
+        CompiledProgram { operations }
+    }
+
+    /// Runs the program with the given initial registers until it halts, and returns its output.
+    ///
+    /// There is no loop detection, so this never returns for a looping program,
+    /// see [`CompiledProgram::run_with_limit`] for a bounded run.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the program reads the reserved combo operand `7`.
+    pub fn run(&self, a: u64, b: u64, c: u64) -> Result<Vec<u8>, ExecutionError> {
+        self.execute(a, b, c, None)
+    }
+
+    /// Runs the program with the given initial registers for at most `max_cycles` instructions, and returns its output.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the program has not halted after `max_cycles` instructions, or reads the reserved combo operand `7`.
+    pub fn run_with_limit(
+        &self,
+        a: u64,
+        b: u64,
+        c: u64,
+        max_cycles: usize,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        self.execute(a, b, c, Some(max_cycles))
+    }
+
+    fn execute(
+        &self,
+        a: u64,
+        b: u64,
+        c: u64,
+        max_cycles: Option<usize>,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        let mut machine = Machine {
+            a,
+            b,
+            c,
+            out: Vec::new(),
+        };
+        let mut p = 0;
+        let mut cycle = 0;
+        while let Some(operation) = self.operations.get(p) {
+            if max_cycles == Some(cycle) {
+                return Err(ExecutionError::FuelExhausted { cycles: cycle });
+            }
+            match operation(&mut machine) {
+                Flow::Next => p += 1,
+                Flow::Jump(target) => p = target,
+                Flow::Reserved => return Err(ExecutionError::ReservedOperand { address: p * 2 }),
+            }
+            cycle += 1;
+        }
+        Ok(machine.out)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn compile(instruction: Instruction, operand: Operand) -> Operation {
+    match instruction {
+        adv => with_combo!(operand, |m, x| {
+            m.a = shift(m.a, x);
+            Flow::Next
+        }),
+        bxl => {
+            let x = Computer::literal(operand);
+            Box::new(move |m| {
+                m.b ^= x;
+                Flow::Next
+            })
+        }
+        bst => with_combo!(operand, |m, x| {
+            m.b = x % 8;
+            Flow::Next
+        }),
+        jnz => {
+            let target = usize::from(operand as u8) / 2;
+            Box::new(move |m| match m.a {
+                0 => Flow::Next,
+                _ => Flow::Jump(target),
+            })
+        }
+        bxc => Box::new(|m| {
+            m.b ^= m.c;
+            Flow::Next
+        }),
+        out => with_combo!(operand, |m, x| {
+            m.out.push((x % 8) as u8);
+            Flow::Next
+        }),
+        bdv => with_combo!(operand, |m, x| {
+            m.b = shift(m.a, x);
+            Flow::Next
+        }),
+        cdv => with_combo!(operand, |m, x| {
+            m.c = shift(m.a, x);
+            Flow::Next
+        }),
+    }
+}
+
+impl Computer {
+    /// Compiles the loaded program, see [`CompiledProgram`].
+    #[must_use]
+    pub fn compile(&self) -> CompiledProgram {
+        CompiledProgram::new(self.program())
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::{assembly, execution::LoopDetection};
+
+    /// Runs the program on the interpreter with the same limit as the compiled one, and returns the output.
+    fn interpret(computer: &Computer, a: u64, b: u64, c: u64) -> Result<Vec<u8>, ExecutionError> {
+        let mut computer = computer.clone();
+        computer.rewind();
+        computer.set_register('a', a);
+        computer.set_register('b', b);
+        computer.set_register('c', c);
+        computer.run_with_limit(1000, LoopDetection::Off)?;
+        Ok(computer.output().to_vec())
+    }
+
+    #[test]
+    fn compiled() {
+        let example = Computer::new(729, 0, 0, "0,1,5,4,3,0").expect("Unable to load program");
+        assert_eq!(
+            example.compile().run(729, 0, 0),
+            Ok(vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0])
+        );
+
+        // Every instruction and combo operand, a loop without output, and a jump into the middle of the program
+        let sources = [
+            "loop: bst a\nbxl 1\ncdv b\nadv 3\nbxc\nout b\njnz loop",
+            "loop: adv 1\nout a\njnz loop",
+            "loop: adv 3\nout a\njnz loop",
+            "bdv 2\ncdv b\nbxc\nout c\nbst 3\nout b\nout 1",
+            "loop: bxl 1\njnz loop",
+            "bxl 7\njnz 3\nout b\nout a",
+        ];
+        let mut programs: Vec<Computer> = sources
+            .iter()
+            .map(|source| {
+                let mut computer = Computer::default();
+                computer.load_program(assembly::assemble(source).expect("Unable to assemble"));
+                computer
+            })
+            .collect();
+        // The reserved combo operand cannot be assembled
+        programs.push(Computer::new(0, 0, 0, "5,4,5,7").expect("Unable to load program"));
+
+        let registers = [
+            (0, 0, 0),
+            (1, 2, 3),
+            (729, 0, 0),
+            (2024, 0, 0),
+            (117_440, 0, 0),
+            (0o7654_3210_1234, 5, 9),
+            (u64::MAX, 63, 1),
+        ];
+        for computer in &programs {
+            let compiled = computer.compile();
+            for (a, b, c) in registers {
+                assert_eq!(
+                    compiled.run_with_limit(a, b, c, 1000),
+                    interpret(computer, a, b, c),
+                    "{} with a = {a}, b = {b}, c = {c}",
+                    computer.print_program()
+                );
+            }
+        }
+    }
+}
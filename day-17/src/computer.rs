@@ -1,4 +1,6 @@
-use std::{collections::HashSet, fmt::Display, mem::transmute, str::FromStr};
+use std::{fmt::Display, str::FromStr};
+
+use crate::assembly::{disassemble, to_numeric};
 
 #[allow(clippy::enum_glob_use)]
 use Instruction::*;
@@ -24,45 +26,62 @@ pub struct Computer {
 }
 
 impl Computer {
-    #[must_use]
-    pub fn new(a: u64, b: u64, c: u64, program: &str) -> Self {
+    /// Creates a computer with the given registers, and loads the program for execution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program cannot be loaded, see [`Computer::load`].
+    pub fn new(a: u64, b: u64, c: u64, program: &str) -> Result<Self, LoadError> {
         let mut computer = Computer {
             a,
             b,
             c,
             ..Default::default()
         };
-        computer.load(program);
-        computer
+        computer.load(program)?;
+        Ok(computer)
     }
 
-    /// Reads the given program string and parses it into an executable `Program`,
-    /// and loads the result for execution.
+    /// Reads the given program string of comma-separated numbers and parses it into an executable `Program`,
+    /// and loads the result for execution. The previously loaded program is kept if loading fails.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the program contains steps that cannot be parsed as integer,
-    /// or if the steps contain integers outside the range `0..=7`
-    pub fn load(&mut self, program: &str) {
-        let steps: Vec<u8> = program
-            .trim()
-            .split(',')
-            .map(|c| c.parse().expect("Unable to parse program step as integer"))
-            .collect();
-        assert!(
-            steps.iter().all(|&n| n <= 7),
-            "Program contains invalid steps, outside the range `0..=7`"
-        );
+    /// Returns an error with the position of the offending number in the program, counted from `0`,
+    /// if a number cannot be parsed, is outside the range `0..=7`, or is an opcode without an operand.
+    pub fn load(&mut self, program: &str) -> Result<(), LoadError> {
+        let source = program.trim();
         let mut program = Vec::new();
-        let mut iter = steps.chunks_exact(2);
-        while let Some(&[instruction, operand]) = iter.next() {
-            unsafe {
-                program.push((
-                    transmute::<u8, Instruction>(instruction),
-                    transmute::<u8, Operand>(operand),
-                ));
+        let mut opcode = None;
+        let tokens = source.split(',').filter(|_| !source.is_empty());
+        for (position, token) in tokens.enumerate() {
+            let token = token.trim();
+            let value: u64 = token.parse().map_err(|_| LoadError::BadToken {
+                position,
+                token: token.to_string(),
+            })?;
+            let out_of_range = |_| LoadError::OutOfRange { position, value };
+            // Values beyond `u8` are just as out of range as `8`
+            let step = u8::try_from(value).unwrap_or(u8::MAX);
+            match opcode.take() {
+                None => opcode = Some(Instruction::try_from(step).map_err(out_of_range)?),
+                Some(instruction) => {
+                    program.push((instruction, Operand::try_from(step).map_err(out_of_range)?));
+                }
             }
         }
+        if opcode.is_some() {
+            return Err(LoadError::DanglingOpcode {
+                position: program.len() * 2,
+            });
+        }
+
+        self.program = program;
+        Ok(())
+    }
+
+    /// Loads an already parsed program for execution, e.g. one built by [`assemble`](crate::assembly::assemble).
+    pub fn load_program(&mut self, program: Program) {
         self.program = program;
     }
 
@@ -75,6 +94,13 @@ impl Computer {
         self.out = Vec::new();
     }
 
+    /// Moves the instruction pointer back to the start of the program and clears the output, keeping the registers.
+    pub fn rewind(&mut self) {
+        self.p = 0;
+        self.jmp = false;
+        self.out.clear();
+    }
+
     pub fn hard_reset(&mut self) {
         *self = Computer::default();
     }
@@ -88,47 +114,54 @@ impl Computer {
         }
     }
 
-    /// Executes the program of this [`Computer`].
-    ///
-    /// # Errors
-    ///
-    /// Errors if the computer detects an infinite loop.
-    pub fn run(&mut self, verbose: bool) -> Result<(), String> {
-        let mut states: HashSet<(u64, u64, u64, usize)> = HashSet::new();
-        states.insert((self.a, self.b, self.c, self.p));
-        let mut cycle = 0;
-        loop {
-            if verbose {
-                println!("Cycle      : {cycle}");
-                println!("{self}");
-            }
-            let Some(&(instruction, operand)) = self.program.get(self.p) else {
-                break;
-            };
-            if verbose {
-                println!("{instruction}");
-                println!("{operand}");
-                println!();
-            }
-            self.cycle(instruction, operand);
-            match self.jmp {
-                true => self.jmp = false,
-                false => self.p += 1,
-            }
-            let new_state = states.insert((self.a, self.b, self.c, self.p));
-            if !new_state {
-                return Err("Computer has detected a loop".to_string());
-            }
-            cycle += 1;
+    /// Executes the instruction at the instruction pointer, and returns it.
+    /// Returns `None` without doing anything if the program has halted.
+    pub fn step(&mut self) -> Option<(Instruction, Operand)> {
+        let &(instruction, operand) = self.program.get(self.p)?;
+        self.cycle(instruction, operand);
+        match self.jmp {
+            true => self.jmp = false,
+            false => self.p += 1,
         }
+        Some((instruction, operand))
+    }
 
-        Ok(())
+    /// Returns the instruction at the instruction pointer, or `None` if the program has halted.
+    #[must_use]
+    pub fn current(&self) -> Option<(Instruction, Operand)> {
+        self.program.get(self.p).copied()
+    }
+
+    #[must_use]
+    pub fn is_halted(&self) -> bool {
+        self.p >= self.program.len()
+    }
+
+    #[must_use]
+    pub fn a(&self) -> u64 {
+        self.a
+    }
+
+    #[must_use]
+    pub fn b(&self) -> u64 {
+        self.b
+    }
+
+    #[must_use]
+    pub fn c(&self) -> u64 {
+        self.c
+    }
+
+    /// Returns the instruction pointer as an address, which counts the numbers of the program like jump targets do.
+    #[must_use]
+    pub fn address(&self) -> usize {
+        self.p * 2
     }
 
     fn cycle(&mut self, instruction: Instruction, operand: Operand) {
         #[allow(clippy::cast_possible_truncation)]
         match instruction {
-            adv => self.a /= 2u64.pow(self.combo(operand) as u32),
+            adv => self.a = shift(self.a, self.combo(operand)),
             bxl => self.b ^= Computer::literal(operand),
             bst => self.b = self.combo(operand) % 8,
             jnz => {
@@ -139,8 +172,8 @@ impl Computer {
             }
             bxc => self.b ^= self.c,
             out => self.out.push((self.combo(operand) % 8) as u8),
-            bdv => self.b = self.a / (2u64.pow(self.combo(operand) as u32)),
-            cdv => self.c = self.a / (2u64.pow(self.combo(operand) as u32)),
+            bdv => self.b = shift(self.a, self.combo(operand)),
+            cdv => self.c = shift(self.a, self.combo(operand)),
         }
     }
 
@@ -155,11 +188,23 @@ impl Computer {
 
     #[must_use]
     pub fn print_program(&self) -> String {
-        self.program
-            .iter()
-            .map(|(i, o)| format!("{i},{o}"))
-            .collect::<Vec<_>>()
-            .join(",")
+        to_numeric(&self.program)
+    }
+
+    #[must_use]
+    pub fn output(&self) -> &[u8] {
+        &self.out
+    }
+
+    #[must_use]
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Renders the loaded program as annotated assembly, see [`disassemble`].
+    #[must_use]
+    pub fn disassemble(&self) -> String {
+        disassemble(&self.program)
     }
 
     #[must_use]
@@ -186,6 +231,16 @@ impl Computer {
     }
 }
 
+/// Divides the value by two to the power of the amount, i.e. shifts it right by the amount.
+/// Shifting by 64 or more bits gives zero, as the quotient would.
+#[must_use]
+pub fn shift(value: u64, amount: u64) -> u64 {
+    u32::try_from(amount)
+        .ok()
+        .and_then(|amount| value.checked_shr(amount))
+        .unwrap_or(0)
+}
+
 impl FromStr for Computer {
     type Err = ParseError;
 
@@ -195,29 +250,25 @@ impl FromStr for Computer {
             return Err(ParseError::Register);
         };
         let mut registers = registers.lines();
-        let a: u64 = parse_register(&mut registers);
-        let b: u64 = parse_register(&mut registers);
-        let c: u64 = parse_register(&mut registers);
+        let a: u64 = parse_register(&mut registers)?;
+        let b: u64 = parse_register(&mut registers)?;
+        let c: u64 = parse_register(&mut registers)?;
 
         let Some((_, program)) = program.split_once(": ") else {
             return Err(ParseError::Program);
         };
 
-        Ok(Computer::new(a, b, c, program))
+        Ok(Computer::new(a, b, c, program)?)
     }
 }
 
 // FIXME: use an actual parser library like nom or winnow
-fn parse_register(registers: &mut std::str::Lines<'_>) -> u64 {
-    let e = ParseError::Register.to_string();
+fn parse_register(registers: &mut std::str::Lines<'_>) -> Result<u64, ParseError> {
     registers
         .next()
-        .expect(&e)
-        .split_once(": ")
-        .expect(&e)
-        .1
-        .parse()
-        .expect(&e)
+        .and_then(|line| line.split_once(": "))
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .ok_or(ParseError::Register)
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -226,18 +277,32 @@ pub enum ParseError {
     Register,
     #[error("Unable to parse program")]
     Program,
+    #[error("Unable to load program: {0}")]
+    Load(#[from] LoadError),
 }
 
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum LoadError {
+    #[error("Unable to parse `{token}` at position {position} as a number")]
+    BadToken { position: usize, token: String },
+    #[error("Value {value} at position {position} is outside the range 0..=7")]
+    OutOfRange { position: usize, value: u64 },
+    #[error("Opcode at position {position} has no operand")]
+    DanglingOpcode { position: usize },
+}
+
+/// Error for a number that is not a valid opcode or operand.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Value {0} is outside the range 0..=7")]
+pub struct OutOfRange(pub u8);
+
 impl std::fmt::Display for Computer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut string = String::new();
-        string.push_str(&format!("Register A : {}\n", self.a));
-        string.push_str(&format!("Register B : {}\n", self.b));
-        string.push_str(&format!("Register C : {}\n", self.c));
-        string.push_str(&format!("Pointer    : {}\n", self.p));
-        string.push_str(&format!("Out        : {:?}", self.out));
-
-        write!(f, "{string}")
+        writeln!(f, "Register A : {}", self.a)?;
+        writeln!(f, "Register B : {}", self.b)?;
+        writeln!(f, "Register C : {}", self.c)?;
+        writeln!(f, "Pointer    : {}", self.p)?;
+        write!(f, "Out        : {:?}", self.out)
     }
 }
 
@@ -255,9 +320,35 @@ pub enum Instruction {
     cdv = 7,
 }
 
+impl Instruction {
+    /// Returns whether the operand of this instruction is a combo operand, rather than a literal one.
+    #[must_use]
+    pub fn takes_combo(self) -> bool {
+        matches!(self, adv | bst | out | bdv | cdv)
+    }
+}
+
+impl TryFrom<u8> for Instruction {
+    type Error = OutOfRange;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(adv),
+            1 => Ok(bxl),
+            2 => Ok(bst),
+            3 => Ok(jnz),
+            4 => Ok(bxc),
+            5 => Ok(out),
+            6 => Ok(bdv),
+            7 => Ok(cdv),
+            _ => Err(OutOfRange(value)),
+        }
+    }
+}
+
 impl Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Instruction: {self:?}")
+        write!(f, "{self:?}")
     }
 }
 
@@ -275,10 +366,81 @@ pub enum Operand {
     op7 = 7,
 }
 
+impl TryFrom<u8> for Operand {
+    type Error = OutOfRange;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(op0),
+            1 => Ok(op1),
+            2 => Ok(op2),
+            3 => Ok(op3),
+            4 => Ok(op4),
+            5 => Ok(op5),
+            6 => Ok(op6),
+            7 => Ok(op7),
+            _ => Err(OutOfRange(value)),
+        }
+    }
+}
+
 impl Display for Operand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "Operand    : {self:?}")
+        write!(f, "{}", *self as u8)
     }
 }
 
-type Program = Vec<(Instruction, Operand)>;
+pub type Program = Vec<(Instruction, Operand)>;
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn load_errors() {
+        let load = |program: &str| Computer::new(0, 0, 0, program).err();
+        assert_eq!(load("0,3,5,4,3,0"), None);
+        assert_eq!(load(""), None);
+        assert_eq!(
+            load("0,3,x,4"),
+            Some(LoadError::BadToken {
+                position: 2,
+                token: "x".to_string()
+            })
+        );
+        assert_eq!(
+            load("0,3,5,8"),
+            Some(LoadError::OutOfRange {
+                position: 3,
+                value: 8
+            })
+        );
+        assert_eq!(
+            load("0,300"),
+            Some(LoadError::OutOfRange {
+                position: 1,
+                value: 300
+            })
+        );
+        assert_eq!(
+            load("0,3,5"),
+            Some(LoadError::DanglingOpcode { position: 2 })
+        );
+        assert_eq!(Instruction::try_from(6), Ok(Instruction::bdv));
+        assert_eq!(Operand::try_from(8), Err(OutOfRange(8)));
+
+        let parse = |input: &str| input.parse::<Computer>().err().map(|e| e.to_string());
+        assert_eq!(
+            parse("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5"),
+            Some("Unable to load program: Opcode at position 2 has no operand".to_string())
+        );
+        assert_eq!(
+            parse("Register A: x\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3"),
+            Some("Unable to parse register".to_string())
+        );
+        assert_eq!(
+            parse("Register A: 1\n\nProgram: 0,3"),
+            Some("Unable to parse register".to_string())
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    assembly::{disassemble_instruction, operand_name},
+    computer::{Computer, Operand},
+};
+
+/// Number of cycles after which `continue` gives up, so that a looping program cannot hang the debugger
+pub const CYCLE_LIMIT: usize = 1_000_000;
+
+/// A value the debugger can watch for changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Watch {
+    A,
+    B,
+    C,
+    /// Number of values output so far
+    Output,
+}
+
+impl Watch {
+    #[must_use]
+    pub fn value(self, computer: &Computer) -> u64 {
+        match self {
+            Watch::A => computer.a(),
+            Watch::B => computer.b(),
+            Watch::C => computer.c(),
+            Watch::Output => computer.output().len() as u64,
+        }
+    }
+}
+
+impl TryFrom<&str> for Watch {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        match name {
+            "a" => Ok(Watch::A),
+            "b" => Ok(Watch::B),
+            "c" => Ok(Watch::C),
+            "out" => Ok(Watch::Output),
+            _ => Err(format!("Unknown watch `{name}`, expected a, b, c or out")),
+        }
+    }
+}
+
+impl Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Watch::A => "a",
+            Watch::B => "b",
+            Watch::C => "c",
+            Watch::Output => "out",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Why the debugger stopped executing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// A single step has been executed
+    Stepped,
+    /// The instruction pointer has moved past the end of the program
+    Halted,
+    /// The instruction pointer has reached the breakpoint at this address
+    Breakpoint(usize),
+    /// A watched value has changed
+    Watchpoint { watch: Watch, old: u64, new: u64 },
+    /// The program ran for [`CYCLE_LIMIT`] cycles without stopping otherwise
+    CycleLimit,
+    /// The instruction at this address reads the reserved combo operand `7`, so it cannot be executed
+    ReservedOperand(usize),
+}
+
+impl Display for Stop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stop::Stepped => write!(f, "Stepped"),
+            Stop::Halted => write!(f, "Halted"),
+            Stop::Breakpoint(address) => write!(f, "Breakpoint at {address:02}"),
+            Stop::Watchpoint { watch, old, new } => {
+                write!(f, "Watchpoint {watch}: {old} -> {new}")
+            }
+            Stop::CycleLimit => write!(f, "Stopped after {CYCLE_LIMIT} cycles"),
+            Stop::ReservedOperand(address) => {
+                write!(f, "Reserved combo operand 7 at {address:02}")
+            }
+        }
+    }
+}
+
+/// Steps through the program of a [`Computer`], stopping at breakpoints and watchpoints.
+pub struct Debugger {
+    computer: Computer,
+    /// Addresses of the breakpoints
+    breakpoints: BTreeSet<usize>,
+    watches: BTreeSet<Watch>,
+}
+
+impl Debugger {
+    #[must_use]
+    pub fn new(computer: Computer) -> Self {
+        Debugger {
+            computer,
+            breakpoints: BTreeSet::new(),
+            watches: BTreeSet::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn computer(&self) -> &Computer {
+        &self.computer
+    }
+
+    pub fn computer_mut(&mut self) -> &mut Computer {
+        &mut self.computer
+    }
+
+    /// Adds a breakpoint at the given address. Returns whether it was not set before.
+    pub fn add_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.insert(address)
+    }
+
+    /// Removes the breakpoint at the given address. Returns whether it was set.
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    pub fn add_watch(&mut self, watch: Watch) -> bool {
+        self.watches.insert(watch)
+    }
+
+    pub fn remove_watch(&mut self, watch: Watch) -> bool {
+        self.watches.remove(&watch)
+    }
+
+    /// Executes a single instruction, and reports the first watched value it changed, if any.
+    /// An instruction reading the reserved combo operand `7` is not executed.
+    pub fn step(&mut self) -> Stop {
+        if let Some((instruction, operand)) = self.computer.current() {
+            if instruction.takes_combo() && operand == Operand::op7 {
+                return Stop::ReservedOperand(self.computer.address());
+            }
+        }
+        let before: Vec<(Watch, u64)> = self
+            .watches
+            .iter()
+            .map(|&watch| (watch, watch.value(&self.computer)))
+            .collect();
+        if self.computer.step().is_none() {
+            return Stop::Halted;
+        }
+        for (watch, old) in before {
+            let new = watch.value(&self.computer);
+            if new != old {
+                return Stop::Watchpoint { watch, old, new };
+            }
+        }
+        match self.computer.is_halted() {
+            true => Stop::Halted,
+            false => Stop::Stepped,
+        }
+    }
+
+    /// Executes instructions until the program halts, reaches a breakpoint or changes a watched value.
+    /// At least one instruction is executed, so that continuing from a breakpoint moves past it.
+    pub fn resume(&mut self) -> Stop {
+        for _ in 0..CYCLE_LIMIT {
+            match self.step() {
+                Stop::Stepped => (),
+                stop => return stop,
+            }
+            let address = self.computer.address();
+            if self.breakpoints.contains(&address) {
+                return Stop::Breakpoint(address);
+            }
+        }
+        Stop::CycleLimit
+    }
+
+    /// Returns the current instruction as a line of annotated assembly, or a note that the program has halted.
+    #[must_use]
+    pub fn current(&self) -> String {
+        match self.computer.current() {
+            Some((instruction, operand)) => {
+                disassemble_instruction(self.computer.address(), instruction, operand)
+            }
+            None => format!("{:02}: halted", self.computer.address()),
+        }
+    }
+
+    /// Renders the whole program, marking the current instruction with `>` and breakpoints with `*`.
+    #[must_use]
+    pub fn listing(&self) -> String {
+        let mut listing = String::new();
+        for (index, &(instruction, operand)) in self.computer.program().iter().enumerate() {
+            let address = index * 2;
+            let current = match address == self.computer.address() {
+                true => '>',
+                false => ' ',
+            };
+            let breakpoint = match self.breakpoints.contains(&address) {
+                true => '*',
+                false => ' ',
+            };
+            listing.push(current);
+            listing.push(breakpoint);
+            listing.push_str(&disassemble_instruction(address, instruction, operand));
+            listing.push('\n');
+        }
+        listing
+    }
+
+    /// Reads commands line by line from `input` and writes the responses to `output`, until `quit` or the end of the input.
+    ///
+    /// Commands:
+    /// - `step [n]` or `s [n]` executes one or `n` instructions, stopping early when the program halts or changes a watched value
+    /// - `continue` or `c` runs until the program halts, reaches a breakpoint or changes a watched value
+    /// - `break ADDRESS` or `b ADDRESS` sets a breakpoint, `delete ADDRESS` removes it
+    /// - `watch a|b|c|out` watches a register or the output length, `unwatch` stops watching it
+    /// - `set a|b|c VALUE` edits a register
+    /// - `print` or `p` shows the registers, output and current instruction
+    /// - `disasm` or `d` shows the whole program
+    /// - `quit` or `q` leaves the debugger
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the input or writing the output fails.
+    pub fn repl(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "{}", self.current())?;
+        for line in input.lines() {
+            let line = line?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[..] {
+                [] => (),
+                ["quit" | "q"] => break,
+                ["step" | "s"] => {
+                    let stop = self.step();
+                    self.report(stop, output)?;
+                }
+                ["step" | "s", count] => match count.parse::<usize>() {
+                    Ok(count) => {
+                        let mut stop = Stop::Stepped;
+                        for _ in 0..count {
+                            stop = self.step();
+                            if stop != Stop::Stepped {
+                                break;
+                            }
+                        }
+                        self.report(stop, output)?;
+                    }
+                    Err(_) => writeln!(output, "Invalid step count `{count}`")?,
+                },
+                ["continue" | "c"] => {
+                    let stop = self.resume();
+                    self.report(stop, output)?;
+                }
+                ["break" | "b", address] => match address.parse() {
+                    Ok(address) => {
+                        self.add_breakpoint(address);
+                        writeln!(output, "Breakpoint set at {address:02}")?;
+                    }
+                    Err(_) => writeln!(output, "Invalid address `{address}`")?,
+                },
+                ["delete", address] => match address.parse().map(|a| self.remove_breakpoint(a)) {
+                    Ok(true) => writeln!(output, "Breakpoint deleted")?,
+                    Ok(false) => writeln!(output, "No breakpoint at `{address}`")?,
+                    Err(_) => writeln!(output, "Invalid address `{address}`")?,
+                },
+                ["watch", name] => match Watch::try_from(name) {
+                    Ok(watch) => {
+                        self.add_watch(watch);
+                        writeln!(output, "Watching {watch}")?;
+                    }
+                    Err(e) => writeln!(output, "{e}")?,
+                },
+                ["unwatch", name] => match Watch::try_from(name) {
+                    Ok(watch) => {
+                        self.remove_watch(watch);
+                        writeln!(output, "Stopped watching {watch}")?;
+                    }
+                    Err(e) => writeln!(output, "{e}")?,
+                },
+                ["set", register @ ("a" | "b" | "c"), value] => match value.parse() {
+                    Ok(value) => {
+                        let register = register.chars().next().unwrap_or_default();
+                        self.computer.set_register(register, value);
+                        writeln!(output, "{register} = {value}")?;
+                    }
+                    Err(_) => writeln!(output, "Invalid value `{value}`")?,
+                },
+                ["print" | "p"] => {
+                    let computer = &self.computer;
+                    writeln!(output, "a = {}", computer.a())?;
+                    writeln!(output, "b = {}", computer.b())?;
+                    writeln!(output, "c = {}", computer.c())?;
+                    writeln!(output, "out = {}", computer.print())?;
+                    if let Some((instruction, operand)) = computer.current() {
+                        // Show the value the operand reads, as registers may have been edited
+                        let name = operand_name(instruction, operand);
+                        let value = match (instruction.takes_combo(), operand as u8) {
+                            (true, 7) => "reserved".to_string(),
+                            (true, _) => computer.combo(operand).to_string(),
+                            (false, _) => Computer::literal(operand).to_string(),
+                        };
+                        writeln!(output, "operand {name} = {value}")?;
+                    }
+                    writeln!(output, "{}", self.current())?;
+                }
+                ["disasm" | "d"] => write!(output, "{}", self.listing())?,
+                _ => writeln!(output, "Unknown command `{line}`")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn report(&self, stop: Stop, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "{stop}")?;
+        writeln!(output, "{}", self.current())
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn debugger() {
+        let mut debugger =
+            Debugger::new(Computer::new(729, 0, 0, "0,1,5,4,3,0").expect("Unable to load program"));
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.computer().a(), 364);
+        debugger.add_breakpoint(2);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(2));
+        assert_eq!(debugger.computer().output(), [4]);
+        assert_eq!(debugger.computer().a(), 182);
+        debugger.add_watch(Watch::A);
+        assert_eq!(
+            debugger.resume(),
+            Stop::Watchpoint {
+                watch: Watch::A,
+                old: 182,
+                new: 91
+            }
+        );
+        debugger.remove_breakpoint(2);
+        debugger.remove_watch(Watch::A);
+        assert_eq!(debugger.resume(), Stop::Halted);
+        assert_eq!(debugger.computer().print(), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(debugger.step(), Stop::Halted);
+
+        let mut debugger =
+            Debugger::new(Computer::new(1, 0, 0, "1,2,5,7").expect("Unable to load program"));
+        assert_eq!(debugger.resume(), Stop::ReservedOperand(2));
+        assert_eq!(debugger.computer().b(), 2);
+        assert_eq!(debugger.step(), Stop::ReservedOperand(2));
+        assert_eq!(debugger.computer().address(), 2);
+        assert!(debugger.computer().output().is_empty());
+    }
+
+    #[test]
+    fn debugger_script() {
+        let mut debugger =
+            Debugger::new(Computer::new(729, 0, 0, "0,1,5,4,3,0").expect("Unable to load program"));
+        let script = "break 4\nwatch out\ncontinue\nprint\nset a 0\nunwatch out\ncontinue\nfly\ndisasm\nquit\nstep\n";
+        let mut output = Vec::new();
+        debugger
+            .repl(script.as_bytes(), &mut output)
+            .expect("Unable to run debugger");
+        let output = String::from_utf8(output).expect("Output is not UTF-8");
+        let expected = "\
+00: adv 1  ; a = a >> 1
+Breakpoint set at 04
+Watching out
+Watchpoint out: 0 -> 1
+04: jnz 0  ; jump to 0 if a != 0
+a = 364
+b = 0
+c = 0
+out = 4
+operand 0 = 0
+04: jnz 0  ; jump to 0 if a != 0
+a = 0
+Stopped watching out
+Halted
+06: halted
+Unknown command `fly`
+  00: adv 1  ; a = a >> 1
+  02: out a  ; output a & 7
+ *04: jnz 0  ; jump to 0 if a != 0
+";
+        assert_eq!(output, expected);
+
+        // Stepping onto a reserved combo operand reports it instead of executing it
+        let mut debugger =
+            Debugger::new(Computer::new(1, 0, 0, "1,2,5,7").expect("Unable to load program"));
+        let mut output = Vec::new();
+        debugger
+            .repl("step\nstep 3\ncontinue\n".as_bytes(), &mut output)
+            .expect("Unable to run debugger");
+        let output = String::from_utf8(output).expect("Output is not UTF-8");
+        let expected = "\
+00: bxl 2  ; b = b ^ 2
+Stepped
+02: out 7  ; reserved combo operand 7
+Reserved combo operand 7 at 02
+02: out 7  ; reserved combo operand 7
+Reserved combo operand 7 at 02
+02: out 7  ; reserved combo operand 7
+";
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashSet, ops::ControlFlow};
+
+use crate::computer::{Computer, Operand};
+
+/// How a run notices that the program is stuck in a loop.
+///
+/// The computer is deterministic, so a loop is found when it returns to a state of registers and
+/// instruction pointer it has been in before. The output is not part of the state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoopDetection {
+    /// No tracking at all, so only a cycle limit can stop a looping program
+    Off,
+    /// Compares each state to a single checkpoint, which moves forward at every power of two cycles,
+    /// as in Brent's cycle detection. Uses constant memory, and finds a loop within a few times its length.
+    Checkpoint,
+    /// Remembers every state, and finds a loop as soon as it repeats. Memory grows with the run time.
+    #[default]
+    Exhaustive,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ExecutionError {
+    #[error("Computer has detected a loop after {cycle} cycles")]
+    LoopDetected { cycle: usize },
+    #[error("Computer has not halted within {cycles} cycles")]
+    FuelExhausted { cycles: usize },
+    #[error("Reserved combo operand 7 at address {address}")]
+    ReservedOperand { address: usize },
+}
+
+type State = (u64, u64, u64, usize);
+
+/// Tracks the states of a run according to a [`LoopDetection`].
+enum Tracker {
+    Off,
+    Checkpoint {
+        checkpoint: State,
+        /// Number of cycles until the checkpoint moves
+        power: usize,
+        /// Number of cycles since the checkpoint moved
+        length: usize,
+    },
+    Exhaustive(HashSet<State>),
+}
+
+impl Tracker {
+    fn new(detection: LoopDetection, initial: State) -> Self {
+        match detection {
+            LoopDetection::Off => Tracker::Off,
+            LoopDetection::Checkpoint => Tracker::Checkpoint {
+                checkpoint: initial,
+                power: 1,
+                length: 0,
+            },
+            LoopDetection::Exhaustive => Tracker::Exhaustive(HashSet::from([initial])),
+        }
+    }
+
+    /// Records the state, and returns whether it has been seen before.
+    fn repeats(&mut self, state: State) -> bool {
+        match self {
+            Tracker::Off => false,
+            Tracker::Checkpoint {
+                checkpoint,
+                power,
+                length,
+            } => {
+                if state == *checkpoint {
+                    return true;
+                }
+                *length += 1;
+                if length == power {
+                    *checkpoint = state;
+                    *power *= 2;
+                    *length = 0;
+                }
+                false
+            }
+            Tracker::Exhaustive(states) => !states.insert(state),
+        }
+    }
+}
+
+impl Computer {
+    /// Executes the program of this [`Computer`], remembering every state to detect loops.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the computer detects an infinite loop, or reads the reserved combo operand `7`.
+    pub fn run(&mut self, verbose: bool) -> Result<(), ExecutionError> {
+        self.execute(None, LoopDetection::Exhaustive, verbose, |_| {
+            ControlFlow::<()>::Continue(())
+        })
+        .map(|_| ())
+    }
+
+    /// Executes the program of this [`Computer`] for at most `max_cycles` instructions, detecting loops as chosen.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the program has not halted after `max_cycles` instructions, if the computer detects
+    /// an infinite loop, or if it reads the reserved combo operand `7`. The computer is left as it was
+    /// when the error occurred, so a run that ran out of cycles can be continued.
+    pub fn run_with_limit(
+        &mut self,
+        max_cycles: usize,
+        detection: LoopDetection,
+    ) -> Result<(), ExecutionError> {
+        self.execute(Some(max_cycles), detection, false, |_| {
+            ControlFlow::<()>::Continue(())
+        })
+        .map(|_| ())
+    }
+
+    /// Executes the program like [`Computer::run_with_limit`], passing each value to `on_output` as soon as
+    /// it is output. Execution stops early when `on_output` returns [`ControlFlow::Break`].
+    ///
+    /// Returns the value of the break, or [`ControlFlow::Continue`] if the program halted.
+    ///
+    /// # Errors
+    ///
+    /// Errors like [`Computer::run_with_limit`], unless `on_output` has stopped the execution first.
+    pub fn run_with_output<B>(
+        &mut self,
+        max_cycles: usize,
+        detection: LoopDetection,
+        on_output: impl FnMut(u8) -> ControlFlow<B>,
+    ) -> Result<ControlFlow<B>, ExecutionError> {
+        self.execute(Some(max_cycles), detection, false, on_output)
+    }
+
+    /// Executes the program, and returns whether it outputs exactly the target.
+    ///
+    /// Stops as soon as an output differs from the target or goes beyond it, so a mismatch is usually found
+    /// long before the program would halt. Loops are detected with [`LoopDetection::Checkpoint`].
+    ///
+    /// # Errors
+    ///
+    /// Errors like [`Computer::run_with_limit`], if the output still matches the target when the error occurs.
+    pub fn matches_output(
+        &mut self,
+        target: &[u8],
+        max_cycles: usize,
+    ) -> Result<bool, ExecutionError> {
+        let mut expected = target.iter();
+        let flow = self.run_with_output(max_cycles, LoopDetection::Checkpoint, |value| {
+            match expected.next() == Some(&value) {
+                true => ControlFlow::Continue(()),
+                false => ControlFlow::Break(()),
+            }
+        })?;
+        Ok(flow.is_continue() && self.output().len() == target.len())
+    }
+
+    /// Returns an iterator which executes the program until each next output, and yields the value.
+    ///
+    /// The iterator ends when the program halts or reaches the reserved combo operand `7`, and the computer
+    /// can be inspected or run further afterwards. It never ends for a program that loops without output.
+    pub fn outputs(&mut self) -> Outputs<'_> {
+        Outputs { computer: self }
+    }
+
+    fn execute<B>(
+        &mut self,
+        max_cycles: Option<usize>,
+        detection: LoopDetection,
+        verbose: bool,
+        mut on_output: impl FnMut(u8) -> ControlFlow<B>,
+    ) -> Result<ControlFlow<B>, ExecutionError> {
+        let state =
+            |computer: &Computer| (computer.a(), computer.b(), computer.c(), computer.address());
+        let mut tracker = Tracker::new(detection, state(self));
+        let mut cycle = 0;
+        while let Some((instruction, operand)) = self.current() {
+            if max_cycles == Some(cycle) {
+                return Err(ExecutionError::FuelExhausted { cycles: cycle });
+            }
+            if instruction.takes_combo() && operand == Operand::op7 {
+                return Err(ExecutionError::ReservedOperand {
+                    address: self.address(),
+                });
+            }
+            let outputs = self.output().len();
+            // Events are only built when printed, as they cost far more than the step itself
+            match verbose {
+                true => {
+                    if let Some(event) = self.step_traced(cycle) {
+                        println!("{event}");
+                    }
+                }
+                false => {
+                    self.step();
+                }
+            }
+            cycle += 1;
+            if let Some(&value) = self.output().get(outputs) {
+                if let ControlFlow::Break(value) = on_output(value) {
+                    return Ok(ControlFlow::Break(value));
+                }
+            }
+            if tracker.repeats(state(self)) {
+                return Err(ExecutionError::LoopDetected { cycle });
+            }
+        }
+
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+/// Iterator over the outputs of a running program, see [`Computer::outputs`].
+pub struct Outputs<'a> {
+    computer: &'a mut Computer,
+}
+
+impl Iterator for Outputs<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (instruction, operand) = self.computer.current()?;
+            if instruction.takes_combo() && operand == Operand::op7 {
+                return None;
+            }
+            let outputs = self.computer.output().len();
+            self.computer.step();
+            if let Some(&value) = self.computer.output().get(outputs) {
+                return Some(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn bounded_execution() {
+        let computer = Computer::new(729, 0, 0, "0,1,5,4,3,0").expect("Unable to load program");
+        let mut limited = computer.clone();
+        assert_eq!(
+            limited.run_with_limit(29, LoopDetection::Off),
+            Err(ExecutionError::FuelExhausted { cycles: 29 })
+        );
+        // The run can be continued where it ran out of cycles
+        assert_eq!(limited.run_with_limit(1, LoopDetection::Off), Ok(()));
+        assert_eq!(limited.print(), "4,6,3,5,6,3,5,2,1,0");
+        let mut limited = computer.clone();
+        assert_eq!(
+            limited.run_with_limit(30, LoopDetection::Checkpoint),
+            Ok(())
+        );
+        assert_eq!(limited.print(), "4,6,3,5,6,3,5,2,1,0");
+
+        // Toggles b forever, returning to the first state every four cycles
+        let looping = Computer::new(1, 0, 0, "1,1,3,0").expect("Unable to load program");
+        let run = |detection| looping.clone().run_with_limit(1000, detection);
+        assert_eq!(
+            run(LoopDetection::Exhaustive),
+            Err(ExecutionError::LoopDetected { cycle: 4 })
+        );
+        // The checkpoint is only compared to once it is inside the loop, at cycle 3
+        assert_eq!(
+            run(LoopDetection::Checkpoint),
+            Err(ExecutionError::LoopDetected { cycle: 7 })
+        );
+        assert_eq!(
+            run(LoopDetection::Off),
+            Err(ExecutionError::FuelExhausted { cycles: 1000 })
+        );
+        assert_eq!(
+            looping.clone().run(false),
+            Err(ExecutionError::LoopDetected { cycle: 4 })
+        );
+
+        let mut reserved = Computer::new(1, 0, 0, "1,2,5,7").expect("Unable to load program");
+        assert_eq!(
+            reserved.run(false),
+            Err(ExecutionError::ReservedOperand { address: 2 })
+        );
+        assert_eq!(reserved.b(), 2);
+    }
+
+    #[test]
+    fn streaming() {
+        let computer = Computer::new(729, 0, 0, "0,1,5,4,3,0").expect("Unable to load program");
+        let mut running = computer.clone();
+        let first: Vec<u8> = running.outputs().take(3).collect();
+        assert_eq!(first, [4, 6, 3]);
+        assert_eq!(running.print(), "4,6,3");
+        let rest: Vec<u8> = running.outputs().collect();
+        assert_eq!(rest, [5, 6, 3, 5, 2, 1, 0]);
+        assert!(running.is_halted());
+
+        let mut stopped = computer.clone();
+        let mut seen = 0;
+        let flow = stopped.run_with_output(1000, LoopDetection::Off, |value| {
+            seen += 1;
+            match value {
+                5 => ControlFlow::Break(seen),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(flow, Ok(ControlFlow::Break(4)));
+        assert_eq!(stopped.print(), "4,6,3,5");
+        assert!(!stopped.is_halted());
+
+        let target = [4, 6, 3, 5, 6, 3, 5, 2, 1, 0];
+        assert_eq!(computer.clone().matches_output(&target, 1000), Ok(true));
+        assert_eq!(
+            computer.clone().matches_output(&target[..9], 1000),
+            Ok(false)
+        );
+        let mut mismatch = computer.clone();
+        assert_eq!(mismatch.matches_output(&[4, 6, 0], 1000), Ok(false));
+        // Stopped at the first wrong output
+        assert_eq!(mismatch.print(), "4,6,3");
+
+        let mut reserved = Computer::new(1, 0, 0, "5,4,5,7").expect("Unable to load program");
+        assert_eq!(reserved.outputs().collect::<Vec<_>>(), [1]);
+        assert_eq!(
+            reserved.matches_output(&[1], 1000),
+            Err(ExecutionError::ReservedOperand { address: 2 })
+        );
+    }
+}
@@ -1,11 +1,38 @@
 use computer::Computer;
+use debugger::Debugger;
 
+pub mod assembly;
+pub mod compiler;
 pub mod computer;
+pub mod debugger;
+pub mod execution;
+pub mod quine;
+pub mod symbolic;
+pub mod trace;
 
 fn main() {
     const INPUT: &str = include_str!("input.txt");
     let parsed = parse(INPUT);
 
+    if std::env::args().nth(1).as_deref() == Some("--debug") {
+        Debugger::new(parsed)
+            .repl(std::io::stdin().lock(), &mut std::io::stdout())
+            .expect("Unable to run debugger");
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("--symbolic") {
+        let paths = parsed
+            .symbolic_paths(1)
+            .expect("Unable to execute symbolically");
+        for path in paths.iter().filter(|path| !path.outputs.is_empty()) {
+            println!("out {}", path.outputs[0]);
+            for condition in &path.conditions {
+                println!("  then {condition}");
+            }
+        }
+        return;
+    }
+
     let value = part1(&parsed);
     println!("Part 1: {value}");
 
@@ -25,20 +52,8 @@ fn part1(data: &ParsedData) -> String {
     computer.print()
 }
 
-fn part2(_data: &ParsedData) -> usize {
-    // FIXME: brute force clearly isn't going to work
-    /* let mut computer = data.clone();
-    let target = computer.print_program();
-    let mut initial_a = 0;
-    loop {
-        computer.set_register('a', initial_a as u64);
-        if computer.run(false).is_ok() && computer.print() == target {
-            break;
-        }
-        initial_a += 1;
-    }
-    initial_a */
-    0
+fn part2(data: &ParsedData) -> u64 {
+    data.find_quine().expect("Unable to find a quine")
 }
 
 #[cfg(test)]
@@ -60,9 +75,15 @@ Program: 0,1,5,4,3,0
 
     #[test]
     fn part2() {
+        const INPUT: &str = r"Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0
+";
         let parsed = crate::parse(INPUT);
         let value = crate::part2(&parsed);
-        let expected = 0;
+        let expected = 117_440;
         assert_eq!(value, expected);
     }
 }
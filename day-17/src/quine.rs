@@ -0,0 +1,113 @@
+use crate::computer::{Computer, Instruction, Operand};
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum QuineError {
+    #[error("Program must end with `jnz 0`")]
+    NoFinalJump,
+    #[error("Program must not jump anywhere but from its end, found {0} jumps")]
+    ExtraJumps(usize),
+    #[error("Program must shift register A exactly once per loop, found {0} shifts")]
+    ShiftCount(usize),
+    #[error("Program must shift register A by the literal 3, found `adv {0}`")]
+    ShiftOperand(Operand),
+    #[error("Program must output exactly once per loop, found {0} outputs")]
+    OutputCount(usize),
+    #[error("No initial value of register A makes the program output itself")]
+    NotFound,
+}
+
+impl Computer {
+    /// Finds the smallest initial value of register A for which the program outputs itself.
+    ///
+    /// The program must have the shape of a single loop which outputs once, shifts A right by 3,
+    /// and jumps back to the start while A is non-zero. Then each output only depends on the
+    /// remaining bits of A, so A is reconstructed 3 bits at a time from the last output backwards,
+    /// backtracking whenever no 3 bits reproduce the outputs so far.
+    /// Registers B and C start from their current values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program has a different structure, or if no value of A works.
+    pub fn find_quine(&self) -> Result<u64, QuineError> {
+        self.check_quine_structure()?;
+        let target: Vec<u8> = self
+            .program()
+            .iter()
+            .flat_map(|&(instruction, operand)| [instruction as u8, operand as u8])
+            .collect();
+        self.extend_quine(0, &target, 1).ok_or(QuineError::NotFound)
+    }
+
+    fn check_quine_structure(&self) -> Result<(), QuineError> {
+        let program = self.program();
+        let count = |instruction| program.iter().filter(|(i, _)| *i == instruction).count();
+        if program.last() != Some(&(Instruction::jnz, Operand::op0)) {
+            return Err(QuineError::NoFinalJump);
+        }
+        match count(Instruction::jnz) {
+            1 => (),
+            jumps => return Err(QuineError::ExtraJumps(jumps)),
+        }
+        match count(Instruction::out) {
+            1 => (),
+            outputs => return Err(QuineError::OutputCount(outputs)),
+        }
+        let shifts: Vec<Operand> = program
+            .iter()
+            .filter(|(i, _)| *i == Instruction::adv)
+            .map(|&(_, o)| o)
+            .collect();
+        match shifts[..] {
+            [Operand::op3] => Ok(()),
+            [operand] => Err(QuineError::ShiftOperand(operand)),
+            _ => Err(QuineError::ShiftCount(shifts.len())),
+        }
+    }
+
+    /// Tries each 3 bits below the already found high bits of A, smallest first, so that they reproduce
+    /// the last `length` outputs, and recurses until the whole target is reproduced.
+    fn extend_quine(&self, high: u64, target: &[u8], length: usize) -> Option<u64> {
+        let suffix = &target[target.len() - length..];
+        for bits in 0..8 {
+            let a = high.checked_mul(8)? | bits;
+            let mut computer = self.clone();
+            computer.rewind();
+            computer.set_register('a', a);
+            // A shrinks by a shift on every iteration, so the program always halts
+            if computer.matches_output(suffix, usize::MAX) != Ok(true) {
+                continue;
+            }
+            if length == target.len() {
+                return Some(a);
+            }
+            if let Some(a) = self.extend_quine(a, target, length + 1) {
+                return Some(a);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn quine_structure() {
+        let structure = |program: &str| {
+            Computer::new(0, 0, 0, program)
+                .expect("Unable to load program")
+                .find_quine()
+        };
+        assert_eq!(
+            structure("0,1,5,4,3,0"),
+            Err(QuineError::ShiftOperand(Operand::op1))
+        );
+        assert_eq!(structure("0,3,5,4"), Err(QuineError::NoFinalJump));
+        assert_eq!(structure("0,3,3,0,5,4,3,0"), Err(QuineError::ExtraJumps(2)));
+        assert_eq!(structure("0,3,3,0"), Err(QuineError::OutputCount(0)));
+        assert_eq!(structure("5,4,3,0"), Err(QuineError::ShiftCount(0)));
+        // Outputs a constant, which can never reproduce the whole program
+        assert_eq!(structure("0,3,5,1,3,0"), Err(QuineError::NotFound));
+    }
+}
@@ -0,0 +1,555 @@
+//! Symbolic execution, where the initial value of register A is unknown.
+//!
+//! Every register holds an [`Expr`] over the unknown `a`, so the program's outputs become expressions
+//! like `(((a & 7) ^ 5) ^ (a >> ((a & 7) ^ 2))) & 7`. Since `jnz` depends on `a`, execution forks into
+//! one [`SymbolicPath`] per outcome. The constraints of a path are then solved bit by bit for `a`.
+
+use std::fmt::Display;
+
+use crate::computer::{shift, Computer, Instruction, Operand};
+
+#[allow(clippy::enum_glob_use)]
+use Instruction::*;
+
+/// Number of jumps after which a path is cut off, so that loops without output cannot run forever
+pub const JUMP_LIMIT: usize = 1024;
+
+/// An expression over the unknown initial value of register A.
+///
+/// Build expressions with [`Expr::shift`], [`Expr::xor`] and [`Expr::and`], which keep them simplified:
+/// constants are folded, and nested shifts and constant masks are merged.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Expr {
+    /// The initial value of register A
+    A,
+    Const(u64),
+    /// Right shift, where shifting by 64 or more bits gives zero
+    Shr(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    #[must_use]
+    pub fn shift(value: Expr, amount: Expr) -> Expr {
+        match (value, amount) {
+            (Expr::Const(v), Expr::Const(s)) => Expr::Const(shift(v, s)),
+            (Expr::Const(0), _) | (_, Expr::Const(64..)) => Expr::Const(0),
+            (value, Expr::Const(0)) => value,
+            (Expr::Shr(inner, first), Expr::Const(second)) => match *first {
+                Expr::Const(first) => {
+                    Expr::shift(*inner, Expr::Const(first.saturating_add(second)))
+                }
+                first => Expr::Shr(
+                    Box::new(Expr::Shr(inner, Box::new(first))),
+                    Box::new(Expr::Const(second)),
+                ),
+            },
+            (value, amount) => Expr::Shr(Box::new(value), Box::new(amount)),
+        }
+    }
+
+    #[must_use]
+    pub fn xor(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (Expr::Const(l), Expr::Const(r)) => Expr::Const(l ^ r),
+            (left, right) if left == right => Expr::Const(0),
+            // Constants go to the right, so that they can be merged
+            (Expr::Const(c), other) => Expr::xor(other, Expr::Const(c)),
+            (value, Expr::Const(0)) => value,
+            (Expr::Xor(inner, first), Expr::Const(second)) => match *first {
+                Expr::Const(first) => Expr::xor(*inner, Expr::Const(first ^ second)),
+                first => Expr::Xor(
+                    Box::new(Expr::Xor(inner, Box::new(first))),
+                    Box::new(Expr::Const(second)),
+                ),
+            },
+            (left, right) => Expr::Xor(Box::new(left), Box::new(right)),
+        }
+    }
+
+    #[must_use]
+    pub fn and(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (Expr::Const(l), Expr::Const(r)) => Expr::Const(l & r),
+            (Expr::Const(c), other) => Expr::and(other, Expr::Const(c)),
+            (_, Expr::Const(0)) => Expr::Const(0),
+            (Expr::And(inner, first), Expr::Const(second)) => match *first {
+                Expr::Const(first) => Expr::and(*inner, Expr::Const(first & second)),
+                first => Expr::And(
+                    Box::new(Expr::And(inner, Box::new(first))),
+                    Box::new(Expr::Const(second)),
+                ),
+            },
+            // Masking after an xor with a constant only needs the masked bits of the constant
+            (Expr::Xor(inner, constant), Expr::Const(mask)) => match *constant {
+                Expr::Const(c) if c & !mask != 0 => {
+                    Expr::and(Expr::xor(*inner, Expr::Const(c & mask)), Expr::Const(mask))
+                }
+                constant => Expr::And(
+                    Box::new(Expr::Xor(inner, Box::new(constant))),
+                    Box::new(Expr::Const(mask)),
+                ),
+            },
+            (left, right) => Expr::And(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// Evaluates the expression for the given initial value of register A.
+    #[must_use]
+    pub fn eval(&self, a: u64) -> u64 {
+        match self {
+            Expr::A => a,
+            Expr::Const(value) => *value,
+            Expr::Shr(value, amount) => shift(value.eval(a), amount.eval(a)),
+            Expr::Xor(left, right) => left.eval(a) ^ right.eval(a),
+            Expr::And(left, right) => left.eval(a) & right.eval(a),
+        }
+    }
+
+    /// Evaluates the expression for a partially known initial value of register A.
+    fn ternary(&self, a: Ternary) -> Ternary {
+        match self {
+            Expr::A => a,
+            Expr::Const(value) => Ternary::known(*value),
+            Expr::Shr(value, amount) => value.ternary(a).shr(amount.ternary(a)),
+            Expr::Xor(left, right) => left.ternary(a).xor(right.ternary(a)),
+            Expr::And(left, right) => left.ternary(a).and(right.ternary(a)),
+        }
+    }
+
+    fn is_compound(&self) -> bool {
+        !matches!(self, Expr::A | Expr::Const(_))
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (left, operator, right) = match self {
+            Expr::A => return write!(f, "a"),
+            Expr::Const(value) => return write!(f, "{value}"),
+            Expr::Shr(left, right) => (left, ">>", right),
+            Expr::Xor(left, right) => (left, "^", right),
+            Expr::And(left, right) => (left, "&", right),
+        };
+        // Nested operations are always parenthesised, so the precedence never needs remembering
+        let operand = |f: &mut std::fmt::Formatter<'_>, e: &Expr| match e.is_compound() {
+            true => write!(f, "({e})"),
+            false => write!(f, "{e}"),
+        };
+        operand(f, left)?;
+        write!(f, " {operator} ")?;
+        operand(f, right)
+    }
+}
+
+/// A condition on the initial value of register A, taken by a `jnz`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Condition {
+    pub expr: Expr,
+    /// Whether the expression is non-zero, i.e. whether the jump is taken
+    pub nonzero: bool,
+}
+
+impl Condition {
+    #[must_use]
+    pub fn holds(&self, a: u64) -> bool {
+        (self.expr.eval(a) != 0) == self.nonzero
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = match self.nonzero {
+            true => "!=",
+            false => "==",
+        };
+        write!(f, "{} {operator} 0", self.expr)
+    }
+}
+
+/// One way through the program, for the initial values of register A that satisfy its conditions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolicPath {
+    /// Expressions of the values output along the path, in order
+    pub outputs: Vec<Expr>,
+    /// Conditions of the jumps taken or not taken along the path, in order
+    pub conditions: Vec<Condition>,
+    /// Whether the path ends by halting, rather than being cut off
+    pub halted: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SymbolicError {
+    #[error("Reserved combo operand 7 at address {0}")]
+    ReservedOperand(usize),
+    #[error("No initial value of register A produces the target output")]
+    NotFound,
+}
+
+#[derive(Clone)]
+struct State {
+    p: usize,
+    a: Expr,
+    b: Expr,
+    c: Expr,
+    jumps: usize,
+    path: SymbolicPath,
+}
+
+impl State {
+    fn combo(&self, operand: Operand) -> Option<Expr> {
+        match operand as u8 {
+            4 => Some(self.a.clone()),
+            5 => Some(self.b.clone()),
+            6 => Some(self.c.clone()),
+            7 => None,
+            value => Some(Expr::Const(u64::from(value))),
+        }
+    }
+}
+
+impl Computer {
+    /// Executes the program from the start with an unknown register A, and the current values of B and C.
+    ///
+    /// Returns every path through the program that outputs at most `max_outputs` values. Paths that
+    /// would output more, or jump more than [`JUMP_LIMIT`] times, are cut off and returned unfinished.
+    /// Paths whose conditions contradict each other are not always detected, so some may be infeasible.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a reachable instruction reads the reserved combo operand `7`.
+    pub fn symbolic_paths(&self, max_outputs: usize) -> Result<Vec<SymbolicPath>, SymbolicError> {
+        let program = self.program();
+        let mut paths = Vec::new();
+        let mut states = vec![State {
+            p: 0,
+            a: Expr::A,
+            b: Expr::Const(self.b()),
+            c: Expr::Const(self.c()),
+            jumps: 0,
+            path: SymbolicPath {
+                outputs: Vec::new(),
+                conditions: Vec::new(),
+                halted: false,
+            },
+        }];
+
+        while let Some(mut state) = states.pop() {
+            let Some(&(instruction, operand)) = program.get(state.p) else {
+                state.path.halted = true;
+                paths.push(state.path);
+                continue;
+            };
+            let combo = state
+                .combo(operand)
+                .ok_or(SymbolicError::ReservedOperand(state.p * 2));
+            let literal = Expr::Const(Computer::literal(operand));
+            match instruction {
+                adv => state.a = Expr::shift(state.a.clone(), combo?),
+                bxl => state.b = Expr::xor(state.b.clone(), literal),
+                bst => state.b = Expr::and(combo?, Expr::Const(7)),
+                bxc => state.b = Expr::xor(state.b.clone(), state.c.clone()),
+                out => {
+                    if state.path.outputs.len() == max_outputs {
+                        paths.push(state.path);
+                        continue;
+                    }
+                    let output = Expr::and(combo?, Expr::Const(7));
+                    state.path.outputs.push(output);
+                }
+                bdv => state.b = Expr::shift(state.a.clone(), combo?),
+                cdv => state.c = Expr::shift(state.a.clone(), combo?),
+                jnz => {
+                    let target = usize::from(operand as u8) / 2;
+                    match state.a {
+                        Expr::Const(0) => (),
+                        _ if state.jumps == JUMP_LIMIT => {
+                            paths.push(state.path);
+                            continue;
+                        }
+                        Expr::Const(_) => {
+                            state.p = target;
+                            state.jumps += 1;
+                            states.push(state);
+                            continue;
+                        }
+                        _ => {
+                            let mut jumped = state.clone();
+                            jumped.path.conditions.push(Condition {
+                                expr: state.a.clone(),
+                                nonzero: true,
+                            });
+                            jumped.p = target;
+                            jumped.jumps += 1;
+                            states.push(jumped);
+                            state.path.conditions.push(Condition {
+                                expr: state.a.clone(),
+                                nonzero: false,
+                            });
+                        }
+                    }
+                }
+            }
+            state.p += 1;
+            states.push(state);
+        }
+
+        Ok(paths)
+    }
+
+    /// Finds the smallest initial value of register A for which the program outputs exactly the target,
+    /// starting with the current values of B and C.
+    ///
+    /// The constraints of each halting path with the right number of outputs are solved bit by bit,
+    /// deciding the bits of A from the most significant one down, and trying 0 before 1. The bits not
+    /// decided yet are unknown, and any constraint already violated by the known bits prunes the search.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program reads the reserved combo operand, or no value of A works.
+    pub fn solve_for_output(&self, target: &[u8]) -> Result<u64, SymbolicError> {
+        let paths = self.symbolic_paths(target.len())?;
+        paths
+            .iter()
+            .filter(|path| path.halted && path.outputs.len() == target.len())
+            .filter_map(|path| solve(path, target))
+            .min()
+            .ok_or(SymbolicError::NotFound)
+    }
+}
+
+/// Finds the smallest value of A that satisfies the conditions of the path and produces the target outputs.
+fn solve(path: &SymbolicPath, target: &[u8]) -> Option<u64> {
+    let consistent = |a: Ternary| {
+        path.conditions.iter().all(|condition| {
+            let value = condition.expr.ternary(a);
+            match condition.nonzero {
+                true => !value.is_zero(),
+                false => value.value == 0,
+            }
+        }) && path.outputs.iter().zip(target).all(|(output, &expected)| {
+            let value = output.ternary(a);
+            (value.value ^ u64::from(expected)) & value.known == 0
+        })
+    };
+    decide(Ternary::unknown(), 64, &consistent)
+}
+
+/// Decides the bits of A below `bits`, the higher ones being known already.
+fn decide(a: Ternary, bits: u32, consistent: &impl Fn(Ternary) -> bool) -> Option<u64> {
+    if !consistent(a) {
+        return None;
+    }
+    let Some(bit) = bits.checked_sub(1) else {
+        return Some(a.value);
+    };
+    [0, 1].into_iter().find_map(|value| {
+        let a = Ternary {
+            known: a.known | 1 << bit,
+            value: a.value | value << bit,
+        };
+        decide(a, bit, consistent)
+    })
+}
+
+/// A 64-bit value where each bit is either known to be 0 or 1, or unknown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Ternary {
+    /// Mask of the known bits
+    known: u64,
+    /// Values of the known bits, with the unknown bits cleared
+    value: u64,
+}
+
+impl Ternary {
+    /// Largest number of unknown bits of a shift amount for which every possible amount is tried
+    const SHIFT_SPLIT: u32 = 6;
+
+    fn known(value: u64) -> Self {
+        Ternary {
+            known: u64::MAX,
+            value,
+        }
+    }
+
+    fn unknown() -> Self {
+        Ternary { known: 0, value: 0 }
+    }
+
+    fn is_zero(self) -> bool {
+        self.known == u64::MAX && self.value == 0
+    }
+
+    fn xor(self, other: Ternary) -> Ternary {
+        let known = self.known & other.known;
+        Ternary {
+            known,
+            value: (self.value ^ other.value) & known,
+        }
+    }
+
+    fn and(self, other: Ternary) -> Ternary {
+        // A bit is known if it is known in both, or known to be 0 in either
+        let zeros = (self.known & !self.value) | (other.known & !other.value);
+        let known = (self.known & other.known) | zeros;
+        Ternary {
+            known,
+            value: self.value & other.value & known,
+        }
+    }
+
+    /// Keeps only the bits that are known and equal in both.
+    fn join(self, other: Ternary) -> Ternary {
+        let known = self.known & other.known & !(self.value ^ other.value);
+        Ternary {
+            known,
+            value: self.value & known,
+        }
+    }
+
+    fn shr(self, amount: Ternary) -> Ternary {
+        let unknown = !amount.known;
+        if unknown.count_ones() > Ternary::SHIFT_SPLIT {
+            return Ternary::unknown();
+        }
+        // Try every amount that agrees with the known bits, by counting through the subsets of the unknown bits
+        let mut result: Option<Ternary> = None;
+        let mut subset = 0u64;
+        loop {
+            let shifted = self.shr_by(amount.value | subset);
+            result = Some(result.map_or(shifted, |r| r.join(shifted)));
+            subset = subset.wrapping_sub(unknown) & unknown;
+            if subset == 0 {
+                break;
+            }
+        }
+        result.unwrap_or_else(Ternary::unknown)
+    }
+
+    fn shr_by(self, amount: u64) -> Ternary {
+        let Some(amount) = u32::try_from(amount).ok().filter(|&a| a < 64) else {
+            return Ternary::known(0);
+        };
+        // The bits shifted in from the top are known zeros
+        let incoming = u64::MAX.checked_shl(64 - amount).unwrap_or(0);
+        Ternary {
+            known: (self.known >> amount) | incoming,
+            value: self.value >> amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::assembly;
+
+    /// A program in the shape of the puzzle inputs, which mixes the low bits of a with higher bits of a
+    /// on every iteration, and shifts a by 3 bits
+    const MIXING: &str = "\
+loop:
+    bst a
+    bxl 1
+    cdv b
+    adv 3
+    bxc
+    out b
+    jnz loop
+";
+
+    fn mixing() -> Computer {
+        let mut computer = Computer::default();
+        computer.load_program(assembly::assemble(MIXING).expect("Unable to assemble"));
+        computer
+    }
+
+    #[test]
+    fn symbolic_outputs() {
+        let computer = mixing();
+        let paths = computer
+            .symbolic_paths(2)
+            .expect("Unable to execute symbolically");
+        let path = paths
+            .iter()
+            .find(|path| path.halted && path.outputs.len() == 2)
+            .expect("No path with two outputs");
+        assert_eq!(
+            path.outputs[0].to_string(),
+            "(((a & 7) ^ 1) ^ (a >> ((a & 7) ^ 1))) & 7"
+        );
+        assert_eq!(
+            path.outputs[1].to_string(),
+            "((((a >> 3) & 7) ^ 1) ^ ((a >> 3) >> (((a >> 3) & 7) ^ 1))) & 7"
+        );
+        let conditions: Vec<String> = path.conditions.iter().map(ToString::to_string).collect();
+        assert_eq!(conditions, ["a >> 3 != 0", "a >> 6 == 0"]);
+
+        // The expressions agree with the interpreter
+        for a in [8, 13, 42, 63] {
+            let mut concrete = computer.clone();
+            concrete.set_register('a', a);
+            concrete.run(false).expect("Program failed");
+            let symbolic: Vec<u8> = path
+                .outputs
+                .iter()
+                .map(|output| u8::try_from(output.eval(a)).expect("Output is masked"))
+                .collect();
+            assert!(path.conditions.iter().all(|c| c.holds(a)));
+            assert_eq!(concrete.output(), symbolic);
+        }
+    }
+
+    #[test]
+    fn symbolic_solver() {
+        // Solving for the output of a known value finds that value or a smaller one with the same output
+        let computer = mixing();
+        for a in [0o7, 0o1234, 0o7654_3210_1234] {
+            let mut concrete = computer.clone();
+            concrete.set_register('a', a);
+            concrete.run(false).expect("Program failed");
+            let target = concrete.output().to_vec();
+            let solution = computer
+                .solve_for_output(&target)
+                .expect("No solution found");
+            assert!(solution <= a);
+            let mut solved = computer.clone();
+            solved.set_register('a', solution);
+            solved.run(false).expect("Program failed");
+            assert_eq!(solved.output(), target);
+        }
+
+        let computer = Computer::new(729, 0, 0, "0,1,5,4,3,0").expect("Unable to load program");
+        // The first shift drops the lowest bit, so 728 is the smallest value with the same output
+        assert_eq!(
+            computer.solve_for_output(&[4, 6, 3, 5, 6, 3, 5, 2, 1, 0]),
+            Ok(728)
+        );
+        assert_eq!(computer.solve_for_output(&[]), Err(SymbolicError::NotFound));
+        let computer = Computer::new(2024, 0, 0, "0,3,5,4,3,0").expect("Unable to load program");
+        assert_eq!(
+            computer.solve_for_output(&[0, 3, 5, 4, 3, 0]),
+            computer.find_quine().map_err(|_| SymbolicError::NotFound)
+        );
+        assert_eq!(computer.solve_for_output(&[0, 3, 5, 4, 3, 0]), Ok(117_440));
+        assert_eq!(computer.solve_for_output(&[1, 0]), Ok(8));
+        let computer = Computer::new(0, 0, 0, "5,7").expect("Unable to load program");
+        assert_eq!(
+            computer.solve_for_output(&[1]),
+            Err(SymbolicError::ReservedOperand(0))
+        );
+    }
+
+    #[test]
+    fn simplification() {
+        let shifted = Expr::shift(Expr::shift(Expr::A, Expr::Const(3)), Expr::Const(4));
+        assert_eq!(shifted, Expr::shift(Expr::A, Expr::Const(7)));
+        assert_eq!(Expr::shift(Expr::A, Expr::Const(70)), Expr::Const(0));
+        let xored = Expr::xor(Expr::xor(Expr::A, Expr::Const(2)), Expr::Const(7));
+        assert_eq!(xored.to_string(), "a ^ 5");
+        assert_eq!(Expr::xor(xored.clone(), xored), Expr::Const(0));
+        let masked = Expr::and(Expr::xor(Expr::A, Expr::Const(13)), Expr::Const(7));
+        assert_eq!(masked.to_string(), "(a ^ 5) & 7");
+        assert_eq!(Expr::shift(Expr::A, Expr::Const(64)).eval(u64::MAX), 0);
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use crate::{
+    assembly::disassemble_instruction,
+    computer::{Computer, Instruction, Operand},
+};
+
+/// Values of the registers at one point of the execution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Registers {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+}
+
+impl Registers {
+    #[must_use]
+    pub fn of(computer: &Computer) -> Self {
+        Registers {
+            a: computer.a(),
+            b: computer.b(),
+            c: computer.c(),
+        }
+    }
+
+    fn to_json(self) -> String {
+        format!("{{\"a\":{},\"b\":{},\"c\":{}}}", self.a, self.b, self.c)
+    }
+}
+
+/// A single executed instruction, with its effect on the computer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// Number of instructions executed before this one
+    pub cycle: usize,
+    /// Address of the instruction
+    pub address: usize,
+    pub instruction: Instruction,
+    pub operand: Operand,
+    /// Value the operand resolved to, i.e. the combo value for combo operands
+    pub value: u64,
+    pub before: Registers,
+    pub after: Registers,
+    /// Value output by the instruction, if any
+    pub output: Option<u8>,
+}
+
+/// Column names of the rows written by [`Event::to_csv`]
+pub const CSV_HEADER: &str =
+    "cycle,address,instruction,operand,value,a_before,b_before,c_before,a_after,b_after,c_after,output";
+
+impl Event {
+    /// Returns the registers changed by the instruction, with their values before and after.
+    pub fn changes(&self) -> impl Iterator<Item = (char, u64, u64)> {
+        [
+            ('a', self.before.a, self.after.a),
+            ('b', self.before.b, self.after.b),
+            ('c', self.before.c, self.after.c),
+        ]
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+    }
+
+    /// Renders the event as a single line JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let output = self
+            .output
+            .map_or_else(|| "null".to_string(), |o| o.to_string());
+        format!(
+            "{{\"cycle\":{},\"address\":{},\"instruction\":\"{}\",\"operand\":{},\"value\":{},\"before\":{},\"after\":{},\"output\":{output}}}",
+            self.cycle,
+            self.address,
+            self.instruction,
+            self.operand,
+            self.value,
+            self.before.to_json(),
+            self.after.to_json(),
+        )
+    }
+
+    /// Renders the event as a CSV row, with the columns of [`CSV_HEADER`]. The output is empty if there is none.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let output = self.output.map(|o| o.to_string()).unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{output}",
+            self.cycle,
+            self.address,
+            self.instruction,
+            self.operand,
+            self.value,
+            self.before.a,
+            self.before.b,
+            self.before.c,
+            self.after.a,
+            self.after.b,
+            self.after.c,
+        )
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let instruction = disassemble_instruction(self.address, self.instruction, self.operand);
+        write!(f, "{:>5} | {instruction} |", self.cycle)?;
+        for (register, before, after) in self.changes() {
+            write!(f, " {register}: {before} -> {after}")?;
+        }
+        if let Some(output) = self.output {
+            write!(f, " out: {output}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Computer {
+    /// Executes the instruction at the instruction pointer, and returns what it did.
+    /// Returns `None` without doing anything if the program has halted, or if the instruction reads
+    /// the reserved combo operand `7`.
+    pub fn step_traced(&mut self, cycle: usize) -> Option<Event> {
+        let (instruction, operand) = self.current()?;
+        let address = self.address();
+        let value = match (instruction.takes_combo(), operand) {
+            (true, Operand::op7) => return None,
+            (true, _) => self.combo(operand),
+            (false, _) => Computer::literal(operand),
+        };
+        let before = Registers::of(self);
+        let outputs = self.output().len();
+        self.step()?;
+        Some(Event {
+            cycle,
+            address,
+            instruction,
+            operand,
+            value,
+            before,
+            after: Registers::of(self),
+            output: self.output().get(outputs).copied(),
+        })
+    }
+
+    /// Returns an iterator which executes the program one instruction at a time, yielding an [`Event`] for each.
+    ///
+    /// The iterator ends when the program halts or reaches the reserved combo operand `7`, so it never ends
+    /// for a looping program.
+    pub fn trace(&mut self) -> Trace<'_> {
+        Trace {
+            computer: self,
+            cycle: 0,
+        }
+    }
+}
+
+/// Iterator over the execution of a program, see [`Computer::trace`].
+pub struct Trace<'a> {
+    computer: &'a mut Computer,
+    cycle: usize,
+}
+
+impl Iterator for Trace<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.computer.step_traced(self.cycle)?;
+        self.cycle += 1;
+        Some(event)
+    }
+}
+
+/// Writes the events as JSON Lines, one object per line.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_json_lines(
+    events: impl IntoIterator<Item = Event>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for event in events {
+        writeln!(out, "{}", event.to_json())?;
+    }
+    Ok(())
+}
+
+/// Writes the events as CSV, starting with the [`CSV_HEADER`].
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_csv(events: impl IntoIterator<Item = Event>, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    for event in events {
+        writeln!(out, "{}", event.to_csv())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn trace() {
+        let mut computer = Computer::new(729, 0, 0, "0,1,5,4,3,0").expect("Unable to load program");
+        let events: Vec<_> = computer.trace().collect();
+        assert_eq!(events.len(), 30);
+        assert_eq!(computer.print(), "4,6,3,5,6,3,5,2,1,0");
+        let outputs: Vec<u8> = events.iter().filter_map(|e| e.output).collect();
+        assert_eq!(outputs, computer.output());
+        // Register A per loop iteration, right after each shift
+        let a: Vec<u64> = events
+            .iter()
+            .filter(|e| e.instruction == Instruction::adv)
+            .map(|e| e.after.a)
+            .take(4)
+            .collect();
+        assert_eq!(a, [364, 182, 91, 45]);
+
+        assert_eq!(
+            events[0].to_string(),
+            "    0 | 00: adv 1  ; a = a >> 1 | a: 729 -> 364"
+        );
+        assert_eq!(
+            events[1].to_json(),
+            r#"{"cycle":1,"address":2,"instruction":"out","operand":4,"value":364,"before":{"a":364,"b":0,"c":0},"after":{"a":364,"b":0,"c":0},"output":4}"#
+        );
+        assert_eq!(events[2].to_csv(), "2,4,jnz,0,0,364,0,0,364,0,0,");
+
+        let mut csv = Vec::new();
+        write_csv(events.iter().copied().take(2), &mut csv).expect("Unable to write CSV");
+        let csv = String::from_utf8(csv).expect("CSV is not UTF-8");
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.starts_with(CSV_HEADER));
+        let mut json = Vec::new();
+        write_json_lines(events, &mut json).expect("Unable to write JSON Lines");
+        let json = String::from_utf8(json).expect("JSON Lines are not UTF-8");
+        assert_eq!(json.lines().count(), 30);
+
+        // The trace ends before an instruction reading the reserved combo operand
+        let mut reserved = Computer::new(1, 0, 0, "1,2,5,7").expect("Unable to load program");
+        let events: Vec<_> = reserved.trace().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].after.b, 2);
+        assert_eq!(reserved.address(), 2);
+        assert_eq!(reserved.step_traced(1), None);
+        assert!(reserved.output().is_empty());
+    }
+}
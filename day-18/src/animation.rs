@@ -0,0 +1,148 @@
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use shared::Coordinate;
+
+use crate::memory::Memory;
+
+/// Tile used for the first byte that cuts off the exit
+pub const BLOCKING_TILE: char = 'X';
+
+/// A snapshot of the memory space after another byte has fallen.
+pub struct Frame {
+    /// Number of bytes that have fallen so far
+    pub number: usize,
+    /// The byte that fell in this frame
+    pub byte: Coordinate,
+    /// Length of the current shortest path in steps, or `None` once the exit is cut off
+    pub path_length: Option<usize>,
+    /// Whether the exit has been cut off by now
+    pub blocked: bool,
+    memory: Memory,
+}
+
+impl Frame {
+    /// Renders this [`Frame`] with ANSI colours, for playing back in a terminal.
+    #[must_use]
+    pub fn ansi(&self) -> String {
+        let mut string = String::new();
+        for character in self.to_string().chars() {
+            match character {
+                'O' => string.push_str("\x1b[32mO\x1b[0m"),
+                '#' => string.push_str("\x1b[90m#\x1b[0m"),
+                BLOCKING_TILE => string.push_str("\x1b[1;31mX\x1b[0m"),
+                other => string.push(other),
+            }
+        }
+        string
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Byte {}: {},{}", self.number, self.byte.x, self.byte.y)?;
+        match self.path_length {
+            Some(length) => writeln!(f, ", shortest path {length}")?,
+            None => writeln!(f, ", exit cut off")?,
+        }
+        write!(f, "{}", self.memory)
+    }
+}
+
+/// Iterator over the [`Frame`]s of the bytes falling into a [`Memory`] one by one.
+///
+/// The shortest path is only recomputed when a byte lands on it. Once a byte cuts off the exit,
+/// it stays highlighted with [`BLOCKING_TILE`] in every following frame.
+pub struct Animation<'a> {
+    memory: Memory,
+    bytes: &'a [Coordinate],
+    fallen: usize,
+    path: Option<Vec<Coordinate>>,
+    blocking: Option<Coordinate>,
+}
+
+impl<'a> Animation<'a> {
+    #[must_use]
+    pub fn new(memory: Memory, bytes: &'a [Coordinate]) -> Self {
+        let path = memory.shortest_path();
+        Animation {
+            memory,
+            bytes,
+            fallen: 0,
+            path,
+            blocking: None,
+        }
+    }
+}
+
+impl Iterator for Animation<'_> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &byte = self.bytes.get(self.fallen)?;
+        self.fallen += 1;
+        self.memory.add_bytes(&[byte]);
+        if self.path.as_ref().is_some_and(|path| path.contains(&byte)) {
+            self.path = self.memory.shortest_path();
+            if self.path.is_none() {
+                self.blocking = Some(byte);
+            }
+        }
+
+        let mut memory = self.memory.clone();
+        if let Some(path) = &self.path {
+            memory.add_path(path);
+        }
+        if let Some(blocking) = self.blocking {
+            memory.mark(blocking, BLOCKING_TILE);
+        }
+        Some(Frame {
+            number: self.fallen,
+            byte,
+            path_length: self.path.as_ref().map(|path| path.len() - 1),
+            blocked: self.blocking.is_some(),
+            memory,
+        })
+    }
+}
+
+/// Plays the frames back as a terminal animation, clearing the screen before each frame.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn play(
+    frames: impl IntoIterator<Item = Frame>,
+    delay: Duration,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for frame in frames {
+        write!(out, "\x1b[H\x1b[2J{}", frame.ansi())?;
+        out.flush()?;
+        thread::sleep(delay);
+    }
+    Ok(())
+}
+
+/// Writes every frame as plain text to its own numbered file in `dir`, creating the directory if needed.
+/// Returns the number of frames written.
+///
+/// # Errors
+///
+/// Returns an error if the directory or a frame file cannot be written.
+pub fn write_frames(frames: impl IntoIterator<Item = Frame>, dir: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let mut count = 0;
+    for frame in frames {
+        fs::write(
+            dir.join(format!("frame-{:05}.txt", frame.number)),
+            frame.to_string(),
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -1,61 +1,197 @@
+use std::path::Path;
+use std::time::Duration;
+
+pub mod animation;
 pub mod memory;
+use animation::Animation;
 use memory::Memory;
 use shared::Coordinate;
 
+const USAGE: &str = "Usage: day-18 [--size WIDTHxHEIGHT] [--falls N] [--start X,Y] [--goal X,Y] [--animate | --frames DIR]";
+
 fn main() {
     const INPUT: &str = include_str!("input.txt");
     let parsed = parse(INPUT);
+    let (settings, mode) = match parse_args(std::env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    match mode {
+        Mode::Solve => (),
+        Mode::Animate => {
+            let frames = Animation::new(settings.memory(), &parsed);
+            animation::play(frames, Duration::from_millis(20), &mut std::io::stdout())
+                .expect("Unable to play animation");
+            return;
+        }
+        Mode::Frames(dir) => {
+            let frames = Animation::new(settings.memory(), &parsed);
+            let count =
+                animation::write_frames(frames, Path::new(&dir)).expect("Unable to write frames");
+            println!("Wrote {count} frames to {dir}");
+            return;
+        }
+    }
 
-    let value = part1(&parsed);
+    let value = part1(&parsed, &settings);
     println!("Part 1: {value}");
 
-    let value = part2(&parsed);
+    let value = part2(&parsed, &settings);
     println!("Part 2: {value}");
 }
 
+/// Puzzle parameters, which differ between the example and the real input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Settings {
+    width: usize,
+    height: usize,
+    /// Number of bytes that have fallen in part 1
+    fall_count: usize,
+    /// Start of the path, the top left corner if not given
+    start: Option<Coordinate>,
+    /// Goal of the path, the bottom right corner if not given
+    goal: Option<Coordinate>,
+}
+
+impl Settings {
+    const REAL: Settings = Settings {
+        width: memory::MEMORY_SIZE,
+        height: memory::MEMORY_SIZE,
+        fall_count: 1024,
+        start: None,
+        goal: None,
+    };
+    #[cfg(test)]
+    const EXAMPLE: Settings = Settings {
+        width: 7,
+        height: 7,
+        fall_count: 12,
+        start: None,
+        goal: None,
+    };
+
+    fn memory(&self) -> Memory {
+        let mut memory = Memory::with_bounds(self.width, self.height);
+        if let Some(start) = self.start {
+            memory = memory.with_start(start);
+        }
+        if let Some(goal) = self.goal {
+            memory = memory.with_goal(goal);
+        }
+        memory
+    }
+}
+
+/// What to do with the input, chosen on the command line
+#[derive(Debug, PartialEq, Eq)]
+enum Mode {
+    /// Print the answers to both parts
+    Solve,
+    /// Play the falling bytes in the terminal
+    Animate,
+    /// Write text frames of the falling bytes to the directory
+    Frames(String),
+}
+
+/// Reads the settings and mode from the command line arguments, starting from the settings of the real input.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<(Settings, Mode), String> {
+    let mut settings = Settings::REAL;
+    let mut mode = Mode::Solve;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for `{arg}`"));
+        match arg.as_str() {
+            "--size" => {
+                let size = value()?;
+                let (width, height) = size
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .ok_or(format!("Invalid size `{size}`"))?;
+                settings.width = width;
+                settings.height = height;
+            }
+            "--falls" => {
+                let count = value()?;
+                settings.fall_count = count
+                    .parse()
+                    .map_err(|_| format!("Invalid fall count `{count}`"))?;
+            }
+            "--start" => {
+                let start = value()?;
+                settings.start =
+                    Some(parse_coordinate(&start).ok_or(format!("Invalid start `{start}`"))?);
+            }
+            "--goal" => {
+                let goal = value()?;
+                settings.goal =
+                    Some(parse_coordinate(&goal).ok_or(format!("Invalid goal `{goal}`"))?);
+            }
+            "--animate" => mode = Mode::Animate,
+            "--frames" => mode = Mode::Frames(value()?),
+            _ => return Err(format!("Unknown argument `{arg}`")),
+        }
+    }
+
+    if settings.width == 0 || settings.height == 0 {
+        return Err("Memory must be at least 1x1".to_string());
+    }
+    let inside = |c: Coordinate| {
+        usize::try_from(c.x).is_ok_and(|x| x < settings.width)
+            && usize::try_from(c.y).is_ok_and(|y| y < settings.height)
+    };
+    for (name, coordinate) in [("Start", settings.start), ("Goal", settings.goal)] {
+        if let Some(c) = coordinate.filter(|&c| !inside(c)) {
+            return Err(format!(
+                "{name} {},{} is outside the {}x{} memory",
+                c.x, c.y, settings.width, settings.height
+            ));
+        }
+    }
+    Ok((settings, mode))
+}
+
+fn parse_coordinate(s: &str) -> Option<Coordinate> {
+    let (x, y) = s.split_once(',')?;
+    Some(Coordinate::new(
+        x.trim().parse().ok()?,
+        y.trim().parse().ok()?,
+    ))
+}
+
 type ParsedData = Vec<Coordinate>;
 
 fn parse(input: &str) -> ParsedData {
     input
         .lines()
-        .map(|l| {
-            let e = "Unable to parse coordinate";
-            let (x, y) = l.split_once(',').expect(e);
-            let x = x.parse().expect(e);
-            let y = y.parse().expect(e);
-            Coordinate::new(x, y)
-        })
+        .map(|l| parse_coordinate(l).expect("Unable to parse coordinate"))
         .collect()
 }
 
-#[cfg(not(test))]
-const PART1_FALL_COUNT: usize = 1024;
-#[cfg(test)]
-const PART1_FALL_COUNT: usize = 12;
-
-fn part1(data: &ParsedData) -> usize {
-    let mut memory = Memory::new();
-    let bytes: Vec<Coordinate> = data.clone().into_iter().take(PART1_FALL_COUNT).collect();
-    memory.add_bytes(&bytes);
+fn part1(data: &ParsedData, settings: &Settings) -> usize {
+    let mut memory = settings.memory();
+    let bytes = &data[..settings.fall_count.min(data.len())];
+    memory.add_bytes(bytes);
 
     let path = memory.shortest_path().expect("No path found");
-    // memory.add_path(&path);
-    // memory.print();
-
     path.len() - 1
 }
 
-fn part2(data: &ParsedData) -> String {
-    let index = (0..data.len())
-        .collect::<Vec<usize>>()
-        .partition_point(|&n| Memory::has_path_after_n_bytes(n, data));
-    let byte = data[index];
+fn part2(data: &ParsedData, settings: &Settings) -> String {
+    let byte = settings
+        .memory()
+        .first_blocking_byte(data)
+        .expect("No path to the exit")
+        .expect("No byte blocks the exit");
     format!("{},{}", byte.x, byte.y)
 }
 
 #[cfg(test)]
 mod integration {
-    const INPUT: &str = r"5,4
+    pub(crate) const INPUT: &str = r"5,4
 4,2
 4,5
 3,0
@@ -85,7 +221,7 @@ mod integration {
     #[test]
     fn part1() {
         let parsed = crate::parse(INPUT);
-        let value = crate::part1(&parsed);
+        let value = crate::part1(&parsed, &crate::Settings::EXAMPLE);
         let expected = 22;
         assert_eq!(value, expected);
     }
@@ -93,7 +229,7 @@ mod integration {
     #[test]
     fn part2() {
         let parsed = crate::parse(INPUT);
-        let value = crate::part2(&parsed);
+        let value = crate::part2(&parsed, &crate::Settings::EXAMPLE);
         let expected = "6,1";
         assert_eq!(value, expected);
     }
@@ -101,10 +237,120 @@ mod integration {
 
 #[cfg(test)]
 mod unit {
+    use crate::memory::Memory;
+    use shared::{Coordinate, Graph};
+
     #[test]
-    fn unit() {
-        let value = 0;
-        let expected = 0;
-        assert_eq!(value, expected);
+    fn custom_bounds() {
+        let memory = Memory::with_bounds(5, 3);
+        assert_eq!(memory.goal(), Coordinate::new(4, 2));
+        let path = memory.shortest_path().expect("No path found");
+        assert_eq!(path.len(), 7);
+
+        let memory = memory
+            .with_start(Coordinate::new(2, 1))
+            .with_goal(Coordinate::new(2, 0));
+        let path = memory.shortest_path().expect("No path found");
+        assert_eq!(path, [Coordinate::new(2, 1), Coordinate::new(2, 0)]);
+    }
+
+    #[test]
+    fn earliest_arrival() {
+        let memory = Memory::with_bounds(5, 1);
+        let far = Coordinate::new(9, 9);
+        let late = [far, far, far, far, Coordinate::new(3, 0)];
+        assert_eq!(memory.earliest_arrival(&late), Some(4));
+        let early = [far, far, far, Coordinate::new(3, 0)];
+        assert_eq!(memory.earliest_arrival(&early), None);
+        assert_eq!(memory.earliest_arrival(&[Coordinate::new(0, 0)]), None);
+
+        let parsed = crate::parse(crate::integration::INPUT);
+        let memory = crate::Settings::EXAMPLE.memory();
+        assert_eq!(memory.earliest_arrival(&parsed), Some(12));
+    }
+
+    #[test]
+    fn animation() {
+        let parsed = crate::parse(crate::integration::INPUT);
+        let frames: Vec<_> =
+            crate::Animation::new(crate::Settings::EXAMPLE.memory(), &parsed).collect();
+        assert_eq!(frames.len(), parsed.len());
+        assert_eq!(frames[11].path_length, Some(22));
+        assert_eq!(frames[11].to_string().matches('O').count(), 23);
+
+        let blocking = frames
+            .iter()
+            .position(|frame| frame.blocked)
+            .expect("No byte blocks the exit");
+        assert_eq!(frames[blocking].byte, Coordinate::new(6, 1));
+        assert_eq!(frames[blocking].path_length, None);
+        let last = frames.last().expect("No frames").to_string();
+        assert_eq!(last.matches(crate::animation::BLOCKING_TILE).count(), 1);
+        assert!(!last.contains('O'));
+    }
+
+    #[test]
+    fn arguments() {
+        let args = |line: &str| crate::parse_args(line.split_whitespace().map(String::from));
+        let (settings, mode) =
+            args("--size 7x7 --falls 12 --frames out").expect("Invalid arguments");
+        assert_eq!(settings, crate::Settings::EXAMPLE);
+        assert_eq!(mode, crate::Mode::Frames("out".to_string()));
+
+        let (settings, mode) =
+            args("--size 7x5 --start 6,0 --goal 0,4").expect("Invalid arguments");
+        assert_eq!((settings.width, settings.height), (7, 5));
+        assert_eq!(settings.fall_count, crate::Settings::REAL.fall_count);
+        assert_eq!(settings.start, Some(Coordinate::new(6, 0)));
+        assert_eq!(settings.goal, Some(Coordinate::new(0, 4)));
+        assert_eq!(mode, crate::Mode::Solve);
+        let path = settings.memory().shortest_path().expect("No path found");
+        assert_eq!(path.len() - 1, 10);
+
+        assert!(args("--size 7").is_err());
+        assert!(args("--falls").is_err());
+        assert!(args("--fly").is_err());
+        assert!(args("--size 0x5").is_err());
+        assert!(args("--size 7x7 --start 7,0").is_err());
+        assert!(args("--size 7x7 --goal 3,-1").is_err());
+        assert!(args("--goal 70,70").is_ok());
+        assert!(args("--goal 71,70").is_err());
+    }
+
+    #[test]
+    fn search_graph() {
+        let parsed = crate::parse(crate::integration::INPUT);
+        let mut memory = crate::Settings::EXAMPLE.memory();
+        memory.add_bytes(&parsed[..12]);
+        let search = memory.search();
+        let goal = search.goal.expect("No path found");
+        assert_eq!(search.tree.depth(goal), 22);
+
+        let graph = Graph::from_search_tree(&search.tree, |c| format!("{},{}", c.x, c.y));
+        assert_eq!(graph.nodes.len(), search.tree.len());
+        assert_eq!(graph.edges.len(), graph.nodes.len() - 1);
+        assert!(graph.edges.iter().all(|edge| edge.cost == 1));
+        assert_eq!(graph.nodes[0].label, "0,0");
+        assert_eq!(graph.nodes[goal].label, "6,6");
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains(&format!("n{goal} [label=\"6,6\"];")));
+    }
+
+    #[test]
+    fn first_blocking_byte() {
+        let mut memory = Memory::with_bounds(3, 3);
+        let bytes = [
+            Coordinate::new(1, 1),
+            Coordinate::new(2, 1),
+            Coordinate::new(1, 2),
+        ];
+        assert_eq!(memory.first_blocking_byte(&bytes), Ok(Some(bytes[2])));
+        assert_eq!(memory.first_blocking_byte(&bytes[..2]), Ok(None));
+
+        // The start is walled in before any byte falls
+        memory.add_bytes(&[Coordinate::new(1, 0), Coordinate::new(0, 1)]);
+        assert!(memory.first_blocking_byte(&bytes).is_err());
+        assert!(memory.first_blocking_byte(&[]).is_err());
     }
 }
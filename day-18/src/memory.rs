@@ -1,29 +1,79 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 
-use shared::{Coordinate, Maze, Visitor};
+use shared::{
+    pathfinding::{self, Search},
+    Coordinate, Direction, GridConnectivity, Maze,
+};
 
-#[cfg(not(test))]
-const MEMORY_WIDTH: usize = 71;
-#[cfg(test)]
-const MEMORY_WIDTH: usize = 7;
+/// Width and height of the real memory space
+pub const MEMORY_SIZE: usize = 71;
 
-#[derive(Default)]
+#[derive(Clone)]
 pub struct Memory {
     maze: Maze,
+    start: Coordinate,
+    goal: Coordinate,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Memory::new()
+    }
 }
 
 impl Memory {
+    /// Creates the real memory space, with the start in the top left and the goal in the bottom right corner.
     #[must_use]
-    #[allow(clippy::cast_possible_wrap)]
     pub fn new() -> Self {
+        Memory::with_bounds(MEMORY_SIZE, MEMORY_SIZE)
+    }
+
+    /// Creates a memory space of the given size, with the start in the top left and the goal in the bottom right corner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the memory space is empty or does not fit in `isize` coordinates.
+    #[must_use]
+    pub fn with_bounds(width: usize, height: usize) -> Self {
+        let e = "Memory size out of range";
+        assert!(width > 0 && height > 0, "Memory size must not be empty");
+        let width = isize::try_from(width).expect(e);
+        let height = isize::try_from(height).expect(e);
         let mut maze = Maze::default();
-        for y in 0..MEMORY_WIDTH {
-            for x in 0..MEMORY_WIDTH {
-                maze.upsert(Coordinate::new(x as isize, y as isize), '.');
+        for y in 0..height {
+            for x in 0..width {
+                maze.upsert(Coordinate::new(x, y), '.');
             }
         }
 
-        Memory { maze }
+        Memory {
+            maze,
+            start: Coordinate::new(0, 0),
+            goal: Coordinate::new(width - 1, height - 1),
+        }
+    }
+
+    #[must_use]
+    pub fn with_start(mut self, start: Coordinate) -> Self {
+        self.start = start;
+        self
+    }
+
+    #[must_use]
+    pub fn with_goal(mut self, goal: Coordinate) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    #[must_use]
+    pub fn start(&self) -> Coordinate {
+        self.start
+    }
+
+    #[must_use]
+    pub fn goal(&self) -> Coordinate {
+        self.goal
     }
 
     pub fn add_bytes(&mut self, bytes: &[Coordinate]) {
@@ -38,98 +88,116 @@ impl Memory {
         }
     }
 
-    /// Returns the shortest path of this [`Memory`].
-    ///
-    /// # Panics
-    ///
-    /// Panics if the lock acquisition for the inner maze fails.
+    /// Marks a single cell with the given tile, e.g. to highlight it when rendering.
+    pub fn mark(&mut self, coordinate: Coordinate, tile: char) {
+        self.maze.upsert(coordinate, tile);
+    }
+
+    /// Returns the shortest path from the start to the goal of this [`Memory`].
     #[must_use]
     pub fn shortest_path(&self) -> Option<Vec<Coordinate>> {
-        let maze = self.maze.clone().make_shareable();
-        let start = Coordinate::new(0, 0);
-        #[allow(clippy::cast_possible_wrap)]
-        let e = (MEMORY_WIDTH - 1) as isize;
-        let end = Coordinate::new(e, e);
-        let mut visited = HashSet::new();
-        let mut queue = VecDeque::new();
-        queue.push_back(PathNode {
-            coordinate: start,
-            parent: None,
-        });
-        visited.insert(start);
-        while let Some(node) = queue.pop_front() {
-            let coordinate = node.coordinate;
-            if coordinate == end {
-                return Some(node.into());
-            }
+        Some(self.search().path()?.states)
+    }
 
-            let visitor = Visitor::new(&maze, coordinate);
-            for next in visitor.coordinates_nwes() {
-                let value = maze.lock().expect("Unable to acquire lock").get(next);
-                if value == Some('.') && !visited.contains(&next) {
-                    visited.insert(next);
-                    queue.push_back(PathNode {
-                        coordinate: next,
-                        parent: Some(Box::new(node.clone())),
-                    });
-                }
-            }
-        }
-        None
+    /// Searches for the shortest path from the start to the goal, and returns the search with every cell it visited,
+    /// e.g. for exporting with [`Graph::from_search_tree`](shared::Graph::from_search_tree).
+    #[must_use]
+    pub fn search(&self) -> Search<Coordinate> {
+        pathfinding::bfs_search(
+            self.start,
+            |&coordinate| {
+                Direction::cardinals()
+                    .map(move |d| coordinate + d.to_displacement())
+                    .filter(|&next| self.maze.get(next) == Some('.'))
+            },
+            |&coordinate| coordinate == self.goal,
+        )
     }
 
     #[must_use]
-    pub fn has_path_after_n_bytes(n: usize, bytes: &[Coordinate]) -> bool {
-        let mut memory = Memory::new();
+    pub fn has_path_after_n_bytes(&self, n: usize, bytes: &[Coordinate]) -> bool {
+        let mut memory = self.clone();
         memory.add_bytes(&bytes[0..=n]);
         memory.shortest_path().is_some()
     }
 
-    /// Returns the print of this [`Memory`].
+    /// Returns the first of the given bytes after which there is no path through this [`Memory`],
+    /// or `None` if a path remains after all of them have fallen.
     ///
-    /// # Panics
+    /// Uses incremental connectivity instead of a search per byte, so it runs in a single pass.
     ///
-    /// Panics if the value at some memory address in the maze cannot be read.
-    pub fn print(&self) {
-        for y in 0..MEMORY_WIDTH {
-            for x in 0..MEMORY_WIDTH {
-                #[allow(clippy::cast_possible_wrap)]
-                let c = self
-                    .maze
-                    .get(Coordinate::new(x as isize, y as isize))
-                    .expect("Memory print error");
-                print!("{c}");
-            }
-            println!();
-        }
+    /// # Errors
+    ///
+    /// Returns an error if there is no path even before any byte has fallen.
+    pub fn first_blocking_byte(&self, bytes: &[Coordinate]) -> Result<Option<Coordinate>, String> {
+        let (min, max) = self
+            .maze
+            .bounds()
+            .ok_or("Memory has no cells".to_string())?;
+        let index = GridConnectivity::first_blocking(
+            min,
+            max,
+            |coordinate| self.maze.get(coordinate) == Some('.'),
+            bytes,
+            self.start,
+            self.goal,
+        )?;
+        Ok(index.map(|index| bytes[index]))
     }
-}
-
-#[derive(Clone, Debug)]
-pub struct PathNode {
-    coordinate: Coordinate,
-    parent: Option<Box<PathNode>>,
-}
 
-impl PathNode {
+    /// Returns the earliest time the goal can be reached while the given bytes are falling,
+    /// or `None` if the goal is unreachable.
+    ///
+    /// Byte `i` lands at time `i`, and the walker leaves the start at time `0`, moving one cell per tick.
+    /// A cell can be entered at time `t` only if no byte has landed on it by then.
     #[must_use]
-    pub fn depth(&self) -> usize {
-        match &self.parent {
-            Some(parent) => 1 + parent.depth(),
-            None => 0,
+    pub fn earliest_arrival(&self, bytes: &[Coordinate]) -> Option<usize> {
+        let mut landings: HashMap<Coordinate, usize> = HashMap::new();
+        for (time, &byte) in bytes.iter().enumerate() {
+            landings.entry(byte).or_insert(time);
+        }
+        let free = |coordinate: Coordinate, time: usize| {
+            self.maze.get(coordinate) == Some('.')
+                && landings
+                    .get(&coordinate)
+                    .is_none_or(|&landing| landing > time)
+        };
+        if !free(self.start, 0) {
+            return None;
         }
-    }
-}
 
-impl From<PathNode> for Vec<Coordinate> {
-    fn from(val: PathNode) -> Self {
-        match &val.parent {
-            Some(parent) => {
-                let mut p: Vec<Coordinate> = (*parent.clone()).into();
-                p.push(val.coordinate);
-                p
+        // Bytes only ever accumulate, so reaching a cell earlier is never worse than reaching it later,
+        // and the time-expanded search only needs to visit each cell at its earliest time.
+        let mut visited: HashSet<Coordinate> = HashSet::from([self.start]);
+        let mut frontier = vec![self.start];
+        let mut time = 0;
+        while !frontier.is_empty() {
+            if frontier.contains(&self.goal) {
+                return Some(time);
             }
-            None => vec![val.coordinate],
+            time += 1;
+            let mut next = Vec::new();
+            for coordinate in frontier {
+                for direction in Direction::cardinals() {
+                    let neighbour = coordinate + direction.to_displacement();
+                    if free(neighbour, time) && visited.insert(neighbour) {
+                        next.push(neighbour);
+                    }
+                }
+            }
+            frontier = next;
         }
+
+        None
+    }
+
+    pub fn print(&self) {
+        print!("{self}");
+    }
+}
+
+impl Display for Memory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.maze)
     }
 }
@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+
+use crate::{Coordinate, Direction};
+
+/// Disjoint sets over the indices `0..n`, with path halving and union by size.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the representative of the set containing `x`.
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns whether they were separate before.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
+
+/// Tracks which open cells of a rectangular grid are connected to each other through their
+/// `N`, `W`, `E`, `S` neighbours, while cells are opened one at a time.
+///
+/// Union-find cannot split sets, so blocking cells online is answered offline instead:
+/// start from the fully blocked grid and open the obstacles in reverse, see [`GridConnectivity::first_blocking`].
+#[derive(Clone, Debug)]
+pub struct GridConnectivity {
+    min: Coordinate,
+    max: Coordinate,
+    width: usize,
+    open: Vec<bool>,
+    sets: UnionFind,
+}
+
+impl GridConnectivity {
+    /// Creates a grid spanning from `min` to `max` inclusive, with every cell closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is not below and to the right of `min`, or the grid does not fit in memory.
+    #[must_use]
+    pub fn new(min: Coordinate, max: Coordinate) -> Self {
+        assert!(
+            min.x <= max.x && min.y <= max.y,
+            "Grid corners out of order"
+        );
+        let width = max.x.abs_diff(min.x) + 1;
+        let height = max.y.abs_diff(min.y) + 1;
+        let cells = width.checked_mul(height).expect("Grid too large");
+        GridConnectivity {
+            min,
+            max,
+            width,
+            open: vec![false; cells],
+            sets: UnionFind::new(cells),
+        }
+    }
+
+    fn index(&self, coordinate: Coordinate) -> Option<usize> {
+        if coordinate.x < self.min.x
+            || coordinate.y < self.min.y
+            || coordinate.x > self.max.x
+            || coordinate.y > self.max.y
+        {
+            return None;
+        }
+        let x = coordinate.x.abs_diff(self.min.x);
+        let y = coordinate.y.abs_diff(self.min.y);
+        Some(y * self.width + x)
+    }
+
+    #[must_use]
+    pub fn is_open(&self, coordinate: Coordinate) -> bool {
+        self.index(coordinate).is_some_and(|i| self.open[i])
+    }
+
+    /// Opens the given cell and connects it to its open neighbours. Cells outside the grid are ignored.
+    pub fn open(&mut self, coordinate: Coordinate) {
+        let Some(index) = self.index(coordinate) else {
+            return;
+        };
+        self.open[index] = true;
+        for direction in Direction::cardinals() {
+            let neighbour = coordinate + direction.to_displacement();
+            if let Some(other) = self.index(neighbour).filter(|&i| self.open[i]) {
+                self.sets.union(index, other);
+            }
+        }
+    }
+
+    /// Returns whether both cells are open and connected to each other.
+    pub fn connected(&mut self, a: Coordinate, b: Coordinate) -> bool {
+        match (self.index(a), self.index(b)) {
+            (Some(a), Some(b)) if self.open[a] && self.open[b] => self.sets.connected(a, b),
+            _ => false,
+        }
+    }
+
+    /// Returns the index of the first obstacle that disconnects `start` from `goal` when the
+    /// obstacles are placed in order, or `None` if they stay connected after every obstacle.
+    ///
+    /// The cells for which `is_open` holds are open before any obstacle is placed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` and `goal` are not connected even before any obstacle is placed.
+    pub fn first_blocking(
+        min: Coordinate,
+        max: Coordinate,
+        is_open: impl Fn(Coordinate) -> bool,
+        obstacles: &[Coordinate],
+        start: Coordinate,
+        goal: Coordinate,
+    ) -> Result<Option<usize>, String> {
+        let blocked: HashSet<Coordinate> = obstacles.iter().copied().collect();
+        let mut grid = GridConnectivity::new(min, max);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let coordinate = Coordinate::new(x, y);
+                if is_open(coordinate) && !blocked.contains(&coordinate) {
+                    grid.open(coordinate);
+                }
+            }
+        }
+        if grid.connected(start, goal) {
+            return Ok(None);
+        }
+
+        // A repeated obstacle only blocks its cell from its first occurrence on
+        let mut first_seen: HashSet<Coordinate> = HashSet::new();
+        let first: Vec<bool> = obstacles.iter().map(|&o| first_seen.insert(o)).collect();
+        for (index, &obstacle) in obstacles.iter().enumerate().rev() {
+            if !first[index] || !is_open(obstacle) {
+                continue;
+            }
+            grid.open(obstacle);
+            if grid.connected(start, goal) {
+                return Ok(Some(index));
+            }
+        }
+
+        Err("Start and goal are not connected before any obstacle is placed".to_string())
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn union_find() {
+        let mut sets = UnionFind::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert!(sets.connected(0, 1));
+        assert!(!sets.connected(1, 3));
+        assert!(sets.union(1, 4));
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(2, 0));
+    }
+
+    #[test]
+    fn grid() {
+        let mut grid = GridConnectivity::new(Coordinate::new(-1, -1), Coordinate::new(1, 1));
+        let a = Coordinate::new(-1, -1);
+        let b = Coordinate::new(1, -1);
+        grid.open(a);
+        grid.open(b);
+        assert!(!grid.connected(a, b));
+        grid.open(Coordinate::new(0, -1));
+        assert!(grid.connected(a, b));
+        assert!(!grid.connected(a, Coordinate::new(5, 5)));
+        assert!(!grid.is_open(Coordinate::new(0, 0)));
+    }
+
+    #[test]
+    fn first_blocking() {
+        let min = Coordinate::new(0, 0);
+        let max = Coordinate::new(2, 2);
+        let obstacles = [
+            Coordinate::new(1, 0),
+            Coordinate::new(1, 1),
+            Coordinate::new(1, 0),
+            Coordinate::new(2, 0),
+            Coordinate::new(1, 2),
+        ];
+        let blocking = GridConnectivity::first_blocking(min, max, |_| true, &obstacles, min, max);
+        assert_eq!(blocking, Ok(Some(4)));
+        let blocking =
+            GridConnectivity::first_blocking(min, max, |_| true, &obstacles[..4], min, max);
+        assert_eq!(blocking, Ok(None));
+
+        // A wall down the middle separates start and goal from the beginning
+        let wall = |coordinate: Coordinate| coordinate.x != 1;
+        let blocking = GridConnectivity::first_blocking(min, max, wall, &obstacles, min, max);
+        assert!(blocking.is_err());
+        let blocking = GridConnectivity::first_blocking(min, max, wall, &[], min, max);
+        assert!(blocking.is_err());
+    }
+}
@@ -1,6 +1,10 @@
+use std::str::FromStr;
+
 #[rustfmt::skip]
 use Direction::{NW, N, NE, W, E, SW, S, SE};
 
+use crate::Displacement;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
     NW,
@@ -13,23 +17,228 @@ pub enum Direction {
     SE,
 }
 
+/// All directions in clockwise order, starting from north
+const COMPASS: [Direction; 8] = [N, NE, E, SE, S, SW, W, NW];
+
 impl TryFrom<char> for Direction {
     type Error = String;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            '^' => Ok(N),
-            '>' => Ok(E),
-            'v' => Ok(S),
-            '<' => Ok(W),
-            // NOTE: diagonals missing for now
+            '^' | 'U' | 'u' | 'N' | 'n' => Ok(N),
+            '>' | 'R' | 'r' | 'E' | 'e' => Ok(E),
+            'v' | 'D' | 'd' | 'S' | 's' => Ok(S),
+            '<' | 'L' | 'l' | 'W' | 'w' => Ok(W),
             d => Err(format!("Unable to parse direction {d}")),
         }
     }
 }
 
+impl FromStr for Direction {
+    type Err = String;
+
+    /// Parses arrows, single letters (`U`/`D`/`L`/`R`, `N`/`S`/`E`/`W`), compass abbreviations like `NE`,
+    /// and words like `up`, `north` or `south-west`. Case-insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Direction::try_from(c);
+        }
+        let word: String = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .collect::<String>()
+            .to_lowercase();
+        match word.as_str() {
+            "north" | "up" => Ok(N),
+            "east" | "right" => Ok(E),
+            "south" | "down" => Ok(S),
+            "west" | "left" => Ok(W),
+            "ne" | "northeast" => Ok(NE),
+            "se" | "southeast" => Ok(SE),
+            "sw" | "southwest" => Ok(SW),
+            "nw" | "northwest" => Ok(NW),
+            _ => Err(format!("Unable to parse direction {s}")),
+        }
+    }
+}
+
 impl Direction {
+    /// All directions in reading order
+    pub const ALL: [Direction; 8] = [NW, N, NE, W, E, SW, S, SE];
+
     pub fn iter() -> impl Iterator<Item = Direction> {
-        [NW, N, NE, W, E, SW, S, SE].iter().copied()
+        Direction::ALL.iter().copied()
+    }
+
+    /// Iterates the four cardinal directions, in the same `N`, `W`, `E`, `S` order as [`Direction::iter`].
+    pub fn cardinals() -> impl Iterator<Item = Direction> {
+        [N, W, E, S].iter().copied()
+    }
+
+    /// Iterates the four diagonal directions, in the same order as [`Direction::iter`].
+    pub fn diagonals() -> impl Iterator<Item = Direction> {
+        [NW, NE, SW, SE].iter().copied()
+    }
+
+    #[must_use]
+    pub fn is_cardinal(self) -> bool {
+        matches!(self, N | W | E | S)
+    }
+
+    #[must_use]
+    pub fn is_diagonal(self) -> bool {
+        !self.is_cardinal()
+    }
+
+    /// Returns the position of this direction on the compass, counting clockwise from north.
+    fn compass_index(self) -> usize {
+        match self {
+            N => 0,
+            NE => 1,
+            E => 2,
+            SE => 3,
+            S => 4,
+            SW => 5,
+            W => 6,
+            NW => 7,
+        }
+    }
+
+    /// Rotates clockwise by the given number of eighth turns.
+    fn rotate(self, eighths: usize) -> Self {
+        COMPASS[(self.compass_index() + eighths) % 8]
+    }
+
+    #[must_use]
+    pub fn rotate_cw(self) -> Self {
+        self.rotate(2)
+    }
+
+    #[must_use]
+    pub fn rotate_ccw(self) -> Self {
+        self.rotate(6)
+    }
+
+    #[must_use]
+    pub fn rotate_cw_45(self) -> Self {
+        self.rotate(1)
+    }
+
+    #[must_use]
+    pub fn rotate_ccw_45(self) -> Self {
+        self.rotate(7)
+    }
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        self.rotate(4)
+    }
+
+    /// Returns the heading of this direction in degrees, clockwise from north.
+    #[must_use]
+    pub fn heading(self) -> u16 {
+        // Compass index is always below 8
+        #[allow(clippy::cast_possible_truncation)]
+        let index = self.compass_index() as u16;
+        index * 45
+    }
+
+    /// Returns the clockwise angle in degrees needed to turn from this direction to `other`, in the range `0..360`.
+    #[must_use]
+    pub fn angle_to(self, other: Direction) -> u16 {
+        (other.heading() + 360 - self.heading()) % 360
+    }
+
+    /// Returns the unit step in this direction. The y axis grows downwards, so north is `(0, -1)`.
+    #[must_use]
+    pub fn to_displacement(self) -> Displacement {
+        match self {
+            NW => Displacement::new(-1, -1),
+            N => Displacement::new(0, -1),
+            NE => Displacement::new(1, -1),
+            W => Displacement::new(-1, 0),
+            E => Displacement::new(1, 0),
+            SW => Displacement::new(-1, 1),
+            S => Displacement::new(0, 1),
+            SE => Displacement::new(1, 1),
+        }
+    }
+
+    /// Returns the direction of the given unit step, or `None` if it is not a unit step.
+    #[must_use]
+    pub fn from_displacement(displacement: Displacement) -> Option<Self> {
+        Direction::iter().find(|d| d.to_displacement() == displacement)
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn rotations() {
+        assert_eq!(N.rotate_cw(), E);
+        assert_eq!(W.rotate_cw(), N);
+        assert_eq!(N.rotate_ccw(), W);
+        assert_eq!(NW.rotate_cw_45(), N);
+        assert_eq!(N.rotate_ccw_45(), NW);
+        for direction in Direction::iter() {
+            assert_eq!(direction.rotate_cw().rotate_ccw(), direction);
+            assert_eq!(direction.opposite().opposite(), direction);
+            assert_eq!(
+                direction.opposite().to_displacement(),
+                -direction.to_displacement()
+            );
+        }
+    }
+
+    #[test]
+    fn angles() {
+        assert_eq!(N.heading(), 0);
+        assert_eq!(SW.heading(), 225);
+        assert_eq!(N.angle_to(E), 90);
+        assert_eq!(E.angle_to(N), 270);
+        assert_eq!(NW.angle_to(NE), 90);
+        assert_eq!(S.angle_to(S), 0);
+    }
+
+    #[test]
+    fn displacements() {
+        for direction in Direction::iter() {
+            let displacement = direction.to_displacement();
+            assert_eq!(Direction::from_displacement(displacement), Some(direction));
+        }
+        assert_eq!(Direction::from_displacement(Displacement::new(0, 0)), None);
+        assert_eq!(Direction::from_displacement(Displacement::new(2, 0)), None);
+    }
+
+    #[test]
+    fn subsets() {
+        assert_eq!(Direction::cardinals().collect::<Vec<_>>(), [N, W, E, S]);
+        assert_eq!(Direction::diagonals().collect::<Vec<_>>(), [NW, NE, SW, SE]);
+        assert!(Direction::cardinals().all(Direction::is_cardinal));
+        assert!(Direction::diagonals().all(Direction::is_diagonal));
+    }
+
+    #[test]
+    fn parsing() {
+        for (s, expected) in [
+            ("^", N),
+            ("U", N),
+            ("d", S),
+            ("L", W),
+            ("E", E),
+            ("north", N),
+            ("Right", E),
+            ("south-west", SW),
+            ("NorthEast", NE),
+            ("se", SE),
+            ("North West", NW),
+        ] {
+            assert_eq!(s.parse::<Direction>(), Ok(expected), "{s}");
+        }
+        assert!("x".parse::<Direction>().is_err());
+        assert!("northnorth".parse::<Direction>().is_err());
     }
 }
@@ -0,0 +1,238 @@
+use std::ops::{BitAnd, BitOr, BitOrAssign, Index, IndexMut, Not, Sub};
+
+use crate::Direction;
+
+/// A compact set of [`Direction`]s, stored as one bit per direction.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DirectionSet(u8);
+
+impl DirectionSet {
+    #[must_use]
+    pub fn new() -> Self {
+        DirectionSet(0)
+    }
+
+    #[must_use]
+    pub fn all() -> Self {
+        DirectionSet(u8::MAX)
+    }
+
+    fn bit(direction: Direction) -> u8 {
+        1 << (direction as u8)
+    }
+
+    /// Adds a direction to the set. Returns whether the direction was newly inserted.
+    pub fn insert(&mut self, direction: Direction) -> bool {
+        let inserted = !self.contains(direction);
+        self.0 |= Self::bit(direction);
+        inserted
+    }
+
+    /// Removes a direction from the set. Returns whether the direction was present.
+    pub fn remove(&mut self, direction: Direction) -> bool {
+        let removed = self.contains(direction);
+        self.0 &= !Self::bit(direction);
+        removed
+    }
+
+    #[must_use]
+    pub fn contains(self, direction: Direction) -> bool {
+        self.0 & Self::bit(direction) != 0
+    }
+
+    #[must_use]
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        DirectionSet(self.0 | other.0)
+    }
+
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        DirectionSet(self.0 & other.0)
+    }
+
+    #[must_use]
+    pub fn difference(self, other: Self) -> Self {
+        DirectionSet(self.0 & !other.0)
+    }
+
+    #[must_use]
+    pub fn complement(self) -> Self {
+        DirectionSet(!self.0)
+    }
+
+    #[must_use]
+    pub fn is_subset(self, other: Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// Iterates the directions in the set, in the same order as [`Direction::iter`].
+    pub fn iter(self) -> impl Iterator<Item = Direction> {
+        Direction::iter().filter(move |&d| self.contains(d))
+    }
+}
+
+impl From<Direction> for DirectionSet {
+    fn from(direction: Direction) -> Self {
+        DirectionSet(Self::bit(direction))
+    }
+}
+
+impl FromIterator<Direction> for DirectionSet {
+    fn from_iter<I: IntoIterator<Item = Direction>>(iter: I) -> Self {
+        let mut set = DirectionSet::new();
+        for direction in iter {
+            set.insert(direction);
+        }
+        set
+    }
+}
+
+impl BitOr for DirectionSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for DirectionSet {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+impl BitAnd for DirectionSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+impl Sub for DirectionSet {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.difference(rhs)
+    }
+}
+
+impl Not for DirectionSet {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        self.complement()
+    }
+}
+
+impl std::fmt::Debug for DirectionSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// A fixed-size map holding one value per [`Direction`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DirectionMap<T>([T; 8]);
+
+impl<T> DirectionMap<T> {
+    pub fn from_fn(f: impl FnMut(Direction) -> T) -> Self {
+        DirectionMap(Direction::ALL.map(f))
+    }
+
+    /// Iterates the entries of the map, in the same order as [`Direction::iter`].
+    pub fn iter(&self) -> impl Iterator<Item = (Direction, &T)> {
+        Direction::iter().zip(self.0.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Direction, &mut T)> {
+        Direction::iter().zip(self.0.iter_mut())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.0.iter()
+    }
+
+    #[must_use]
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> DirectionMap<U> {
+        DirectionMap(self.0.map(f))
+    }
+}
+
+impl<T> Index<Direction> for DirectionMap<T> {
+    type Output = T;
+
+    fn index(&self, direction: Direction) -> &Self::Output {
+        &self.0[direction as usize]
+    }
+}
+
+impl<T> IndexMut<Direction> for DirectionMap<T> {
+    fn index_mut(&mut self, direction: Direction) -> &mut Self::Output {
+        &mut self.0[direction as usize]
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::Direction::{E, N, NE, S, SW, W};
+
+    #[test]
+    fn set_operations() {
+        let mut set = DirectionSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(N));
+        assert!(!set.insert(N));
+        set.insert(E);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(E));
+        assert!(!set.contains(S));
+
+        let cardinals: DirectionSet = Direction::cardinals().collect();
+        let diagonals: DirectionSet = Direction::diagonals().collect();
+        assert_eq!(cardinals | diagonals, DirectionSet::all());
+        assert_eq!(cardinals & diagonals, DirectionSet::new());
+        assert_eq!(!cardinals, diagonals);
+        assert_eq!(cardinals - set, [W, S].into_iter().collect());
+        assert!(set.is_subset(cardinals));
+        assert!(!cardinals.is_subset(set));
+
+        assert!(set.remove(N));
+        assert!(!set.remove(N));
+        assert_eq!(set, DirectionSet::from(E));
+        assert_eq!(format!("{set:?}"), "{E}");
+    }
+
+    #[test]
+    fn set_iteration_order() {
+        let set: DirectionSet = [SW, N, E, NE].into_iter().collect();
+        assert_eq!(set.iter().collect::<Vec<_>>(), [N, NE, E, SW]);
+    }
+
+    #[test]
+    fn map() {
+        let mut map: DirectionMap<usize> = DirectionMap::default();
+        map[N] += 1;
+        map[S] += 2;
+        assert_eq!(map[N], 1);
+        assert_eq!(map[S], 2);
+        assert_eq!(map.values().sum::<usize>(), 3);
+
+        let headings = DirectionMap::from_fn(Direction::heading);
+        assert_eq!(headings[W], 270);
+        assert!(headings.iter().all(|(d, &h)| d.heading() == h));
+        let doubled = headings.map(|h| h * 2);
+        assert_eq!(doubled[E], 180);
+    }
+}
@@ -0,0 +1,166 @@
+//! Lattice geometry helpers for [`Coordinate`]s and [`Displacement`]s.
+//!
+//! Polygons are given as their vertices in order; the closing edge from the last vertex
+//! back to the first is implied.
+
+use crate::{math, Coordinate, Displacement};
+
+/// Returns the smallest lattice step in the direction of the displacement, i.e. the displacement divided
+/// by the gcd of its components. The zero displacement is returned as is.
+#[must_use]
+pub fn primitive(displacement: Displacement) -> Displacement {
+    match math::gcd(displacement.x, displacement.y) {
+        Some(0) => displacement,
+        Some(g) => displacement / g,
+        // The gcd only overflows when every non-zero component is `isize::MIN`
+        None => displacement.map(isize::signum),
+    }
+}
+
+#[must_use]
+pub fn manhattan(a: Coordinate, b: Coordinate) -> usize {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+}
+
+#[must_use]
+pub fn chebyshev(a: Coordinate, b: Coordinate) -> usize {
+    a.x.abs_diff(b.x).max(a.y.abs_diff(b.y))
+}
+
+/// Returns every lattice point on the segment from `a` to `b`, both ends included.
+#[must_use]
+pub fn segment(a: Coordinate, b: Coordinate) -> Vec<Coordinate> {
+    let step = primitive(b - a);
+    let mut points = vec![a];
+    let mut point = a;
+    while point != b {
+        point += step;
+        points.push(point);
+    }
+    points
+}
+
+/// Returns whether the three coordinates lie on a single line.
+#[must_use]
+pub fn collinear(a: Coordinate, b: Coordinate, c: Coordinate) -> bool {
+    cross(a, b, c) == 0
+}
+
+/// Returns the z component of the cross product of `b - a` and `c - a`.
+fn cross(a: Coordinate, b: Coordinate, c: Coordinate) -> i128 {
+    let (ax, ay) = (a.x as i128, a.y as i128);
+    let (bx, by) = (b.x as i128, b.y as i128);
+    let (cx, cy) = (c.x as i128, c.y as i128);
+    (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+}
+
+/// Returns twice the area of the polygon, using the shoelace formula.
+///
+/// Twice the area of a lattice polygon is always an integer.
+#[must_use]
+pub fn double_area(polygon: &[Coordinate]) -> u128 {
+    let Some(&origin) = polygon.first() else {
+        return 0;
+    };
+    let sum: i128 = polygon
+        .windows(2)
+        .map(|pair| cross(origin, pair[0], pair[1]))
+        .sum();
+    sum.unsigned_abs()
+}
+
+/// Returns the number of lattice points on the edges of the polygon.
+#[must_use]
+pub fn boundary_points(polygon: &[Coordinate]) -> u128 {
+    match polygon.len() {
+        0 => 0,
+        1 => 1,
+        _ => polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .map(|(a, b)| {
+                // Differences of `isize`s fit an `i128`, so the gcd cannot overflow
+                let dx = a.x.abs_diff(b.x) as i128;
+                let dy = a.y.abs_diff(b.y) as i128;
+                math::gcd(dx, dy).map_or(0, i128::unsigned_abs)
+            })
+            .sum(),
+    }
+}
+
+/// Returns the number of lattice points strictly inside the polygon, using Pick's theorem.
+///
+/// The polygon must be simple, i.e. its edges must not cross.
+#[must_use]
+pub fn interior_points(polygon: &[Coordinate]) -> u128 {
+    // Pick's theorem: A = I + B / 2 - 1, so 2I = 2A - B + 2
+    (double_area(polygon) + 2).saturating_sub(boundary_points(polygon)) / 2
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn primitives() {
+        assert_eq!(
+            primitive(Displacement::new(4, -6)),
+            Displacement::new(2, -3)
+        );
+        assert_eq!(
+            primitive(Displacement::new(0, -5)),
+            Displacement::new(0, -1)
+        );
+        assert_eq!(primitive(Displacement::new(0, 0)), Displacement::new(0, 0));
+        assert_eq!(
+            primitive(Displacement::new(isize::MIN, 0)),
+            Displacement::new(-1, 0)
+        );
+    }
+
+    #[test]
+    fn distances() {
+        let a = Coordinate::new(-1, 2);
+        let b = Coordinate::new(3, -5);
+        assert_eq!(manhattan(a, b), 11);
+        assert_eq!(chebyshev(a, b), 7);
+    }
+
+    #[test]
+    fn segments() {
+        let points = segment(Coordinate::new(0, 0), Coordinate::new(6, -4));
+        let expected = [
+            Coordinate::new(0, 0),
+            Coordinate::new(3, -2),
+            Coordinate::new(6, -4),
+        ];
+        assert_eq!(points, expected);
+        assert_eq!(segment(expected[0], expected[0]), [expected[0]]);
+        assert!(points
+            .iter()
+            .all(|&p| collinear(expected[0], expected[2], p)));
+        assert!(!collinear(expected[0], expected[2], Coordinate::new(1, 1)));
+    }
+
+    #[test]
+    fn polygons() {
+        let square = [
+            Coordinate::new(0, 0),
+            Coordinate::new(4, 0),
+            Coordinate::new(4, 4),
+            Coordinate::new(0, 4),
+        ];
+        assert_eq!(double_area(&square), 32);
+        assert_eq!(boundary_points(&square), 16);
+        assert_eq!(interior_points(&square), 9);
+
+        let triangle = [
+            Coordinate::new(-2, 0),
+            Coordinate::new(2, 0),
+            Coordinate::new(0, 3),
+        ];
+        assert_eq!(double_area(&triangle), 12);
+        assert_eq!(boundary_points(&triangle), 6);
+        assert_eq!(interior_points(&triangle), 4);
+    }
+}
@@ -0,0 +1,264 @@
+//! Export of grid and search structures as graphs, for visualising them with Graphviz or `GraphML` tools.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::{pathfinding::SearchTree, Coordinate, Direction, Maze};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphNode {
+    pub label: String,
+}
+
+/// An edge between two nodes, given by their indices in [`Graph::nodes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub cost: usize,
+}
+
+/// A labelled graph with weighted edges, which can be rendered as Graphviz DOT or `GraphML`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub directed: bool,
+}
+
+/// Returns the coordinates of the maze in reading order, so that exports are deterministic.
+fn reading_order(maze: &Maze) -> Vec<Coordinate> {
+    let mut coordinates = maze.all_coordinates();
+    coordinates.sort_by_key(|c| (c.y, c.x));
+    coordinates
+}
+
+fn label(coordinate: Coordinate) -> String {
+    format!("{},{}", coordinate.x, coordinate.y)
+}
+
+impl Graph {
+    /// Adds a node with the given label, and returns its index.
+    pub fn add_node(&mut self, label: impl Into<String>) -> usize {
+        self.nodes.push(GraphNode {
+            label: label.into(),
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, cost: usize) {
+        self.edges.push(GraphEdge { from, to, cost });
+    }
+
+    /// Builds the undirected graph of the passable cells of a maze, labelled with their coordinates.
+    /// Every pair of `N`, `W`, `E`, `S` neighbours is connected with a cost of 1.
+    #[must_use]
+    pub fn from_maze(maze: &Maze, is_passable: impl Fn(char) -> bool) -> Self {
+        let mut graph = Graph::default();
+        let mut ids: HashMap<Coordinate, usize> = HashMap::new();
+        for coordinate in reading_order(maze) {
+            if maze.get(coordinate).is_some_and(&is_passable) {
+                ids.insert(coordinate, graph.add_node(label(coordinate)));
+            }
+        }
+        for coordinate in reading_order(maze) {
+            let Some(&from) = ids.get(&coordinate) else {
+                continue;
+            };
+            // Only looking forward adds every edge once
+            for direction in [Direction::E, Direction::S] {
+                if let Some(&to) = ids.get(&(coordinate + direction.to_displacement())) {
+                    graph.add_edge(from, to, 1);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Builds the undirected adjacency graph of the regions of a maze, where a region is a connected
+    /// area of the same character. Nodes are labelled with the character, the first coordinate of the
+    /// region in reading order and its area. Edges are weighted with the length of the shared border.
+    #[must_use]
+    pub fn regions(maze: &Maze) -> Self {
+        let mut graph = Graph::default();
+        let mut region_of: HashMap<Coordinate, usize> = HashMap::new();
+        for coordinate in reading_order(maze) {
+            if region_of.contains_key(&coordinate) {
+                continue;
+            }
+            let Some(character) = maze.get(coordinate) else {
+                continue;
+            };
+            let id = graph.nodes.len();
+            let mut stack = vec![coordinate];
+            let mut area = 0;
+            region_of.insert(coordinate, id);
+            while let Some(cell) = stack.pop() {
+                area += 1;
+                for direction in Direction::cardinals() {
+                    let next = cell + direction.to_displacement();
+                    if maze.get(next) == Some(character) && !region_of.contains_key(&next) {
+                        region_of.insert(next, id);
+                        stack.push(next);
+                    }
+                }
+            }
+            graph.add_node(format!("{character} at {} ({area})", label(coordinate)));
+        }
+
+        let mut borders: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        for coordinate in reading_order(maze) {
+            let from = region_of[&coordinate];
+            for direction in [Direction::E, Direction::S] {
+                let next = coordinate + direction.to_displacement();
+                match region_of.get(&next) {
+                    Some(&to) if to != from => {
+                        *borders.entry((from.min(to), from.max(to))).or_default() += 1;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        for ((from, to), length) in borders {
+            graph.add_edge(from, to, length);
+        }
+        graph
+    }
+
+    /// Builds the directed graph of a search tree, with an edge from each node to every node reached from it.
+    /// Edges are weighted with the cost of the step.
+    ///
+    /// Trees come from [`explore`](crate::pathfinding::explore), or from the [`Search`](crate::pathfinding::Search)
+    /// returned by [`bfs_search`](crate::pathfinding::bfs_search) and [`astar_search`](crate::pathfinding::astar_search).
+    #[must_use]
+    pub fn from_search_tree<S: Clone>(tree: &SearchTree<S>, label: impl Fn(&S) -> String) -> Self {
+        let mut graph = Graph {
+            directed: true,
+            ..Graph::default()
+        };
+        for id in 0..tree.len() {
+            graph.add_node(label(tree.state(id)));
+            if let Some(parent) = tree.parent(id) {
+                graph.add_edge(parent, id, tree.cost(id) - tree.cost(parent));
+            }
+        }
+        graph
+    }
+
+    /// Renders this [`Graph`] in the Graphviz DOT language.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let (kind, arrow) = match self.directed {
+            true => ("digraph", "->"),
+            false => ("graph", "--"),
+        };
+        let mut dot = format!("{kind} {{\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(dot, "    n{id} [label=\"{}\"];", escape_dot(&node.label));
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    n{} {arrow} n{} [label=\"{}\"];",
+                edge.from, edge.to, edge.cost
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders this [`Graph`] as a `GraphML` document, with the node labels and edge costs as data keys.
+    #[must_use]
+    pub fn to_graphml(&self) -> String {
+        let direction = match self.directed {
+            true => "directed",
+            false => "undirected",
+        };
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"cost\" for=\"edge\" attr.name=\"cost\" attr.type=\"long\"/>\n",
+        ));
+        let _ = writeln!(xml, "  <graph id=\"G\" edgedefault=\"{direction}\">");
+        for (id, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(
+                xml,
+                "    <node id=\"n{id}\"><data key=\"label\">{}</data></node>",
+                escape_xml(&node.label)
+            );
+        }
+        for (id, edge) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                xml,
+                "    <edge id=\"e{id}\" source=\"n{}\" target=\"n{}\"><data key=\"cost\">{}</data></edge>",
+                edge.from, edge.to, edge.cost
+            );
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(label: &str) -> String {
+    label
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::pathfinding;
+
+    #[test]
+    fn maze_dot() {
+        let maze: Maze = "..\n#.".parse().expect("Unable to parse maze");
+        let graph = Graph::from_maze(&maze, |c| c != '#');
+        let expected = "graph {
+    n0 [label=\"0,0\"];
+    n1 [label=\"1,0\"];
+    n2 [label=\"1,1\"];
+    n0 -- n1 [label=\"1\"];
+    n1 -- n2 [label=\"1\"];
+}
+";
+        assert_eq!(graph.to_dot(), expected);
+    }
+
+    #[test]
+    fn region_adjacency() {
+        let maze: Maze = "AAB\nACB\nAAB".parse().expect("Unable to parse maze");
+        let graph = Graph::regions(&maze);
+        let labels: Vec<&str> = graph.nodes.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(labels, ["A at 0,0 (5)", "B at 2,0 (3)", "C at 1,1 (1)"]);
+        let edges: Vec<(usize, usize, usize)> =
+            graph.edges.iter().map(|e| (e.from, e.to, e.cost)).collect();
+        // A borders B along the top and bottom rows, and surrounds C on three sides
+        assert_eq!(edges, [(0, 1, 2), (0, 2, 3), (1, 2, 1)]);
+    }
+
+    #[test]
+    fn search_tree_graphml() {
+        let tree = pathfinding::explore(0usize, |&n| match n {
+            0 => vec![(1, 5), (2, 1)],
+            2 => vec![(1, 1)],
+            _ => vec![],
+        });
+        let graph = Graph::from_search_tree(&tree, |n| format!("<{n}>"));
+        assert!(graph.directed);
+        assert_eq!(graph.nodes.len(), 3);
+        let xml = graph.to_graphml();
+        assert!(xml.contains("edgedefault=\"directed\""));
+        assert!(xml.contains("<data key=\"label\">&lt;1&gt;</data>"));
+        // The cheapest route to 1 goes through 2, with steps costing 1 each
+        assert!(graph.edges.iter().all(|e| e.cost == 1));
+        assert!(graph.to_dot().starts_with("digraph {\n"));
+    }
+}
@@ -1,9 +1,20 @@
 use nalgebra::{Point2, Vector2};
 
+pub mod connectivity;
+pub use connectivity::*;
 pub mod direction;
 pub use direction::*;
+pub mod direction_set;
+pub use direction_set::*;
+pub mod geometry;
+pub mod graph;
+pub use graph::*;
+pub mod math;
 pub mod maze;
 pub use maze::*;
+pub mod pathfinding;
+pub mod scheduler;
+pub use scheduler::*;
 pub mod visitor;
 pub use visitor::*;
 
@@ -0,0 +1,291 @@
+//! Overflow-checked number theory helpers.
+//!
+//! Every function returns `None` instead of panicking or wrapping when the result,
+//! or an intermediate value, does not fit in the integer type.
+
+use std::fmt::Debug;
+
+/// Signed integer types supported by the helpers in this module.
+pub trait Integer: Copy + Ord + Debug {
+    const ZERO: Self;
+    const ONE: Self;
+    const TWO: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    fn checked_rem_euclid(self, rhs: Self) -> Option<Self>;
+    fn checked_abs(self) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const TWO: Self = 2;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_div(self, rhs)
+                }
+                fn checked_rem(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_rem(self, rhs)
+                }
+                fn checked_rem_euclid(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_rem_euclid(self, rhs)
+                }
+                fn checked_abs(self) -> Option<Self> {
+                    <$t>::checked_abs(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i64, isize, i128);
+
+/// Returns the non-negative greatest common divisor of `a` and `b`.
+#[must_use]
+pub fn gcd<T: Integer>(mut a: T, mut b: T) -> Option<T> {
+    while b != T::ZERO {
+        // The only overflowing remainder is `MIN % -1`, which is zero
+        let r = a.checked_rem(b).unwrap_or(T::ZERO);
+        a = b;
+        b = r;
+    }
+    a.checked_abs()
+}
+
+/// Returns the non-negative least common multiple of `a` and `b`.
+#[must_use]
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    let g = gcd(a, b)?;
+    a.checked_div(g)?.checked_mul(b)?.checked_abs()
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y == g`, where `g` is the non-negative gcd of `a` and `b`.
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn extended_gcd<T: Integer>(a: T, b: T) -> Option<(T, T, T)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r.checked_div(r)?;
+        (old_r, r) = (r, old_r.checked_sub(q.checked_mul(r)?)?);
+        (old_x, x) = (x, old_x.checked_sub(q.checked_mul(x)?)?);
+        (old_y, y) = (y, old_y.checked_sub(q.checked_mul(y)?)?);
+    }
+    if old_r < T::ZERO {
+        let zero = T::ZERO;
+        return Some((
+            zero.checked_sub(old_r)?,
+            zero.checked_sub(old_x)?,
+            zero.checked_sub(old_y)?,
+        ));
+    }
+    Some((old_r, old_x, old_y))
+}
+
+/// Returns the inverse of `a` modulo `m` in the range `0..m`,
+/// or `None` if `m` is not positive or `a` and `m` are not coprime.
+#[must_use]
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.checked_rem_euclid(m)?, m)?;
+    if g != T::ONE {
+        return None;
+    }
+    x.checked_rem_euclid(m)
+}
+
+/// Adds two residues in `0..m` without overflowing.
+fn add_mod<T: Integer>(a: T, b: T, m: T) -> Option<T> {
+    let room = m.checked_sub(b)?;
+    if a >= room {
+        a.checked_sub(room)
+    } else {
+        a.checked_add(b)
+    }
+}
+
+/// Returns `a * b` modulo `m` in the range `0..m`, even when the plain product would overflow.
+#[must_use]
+pub fn mul_mod<T: Integer>(a: T, b: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    let mut a = a.checked_rem_euclid(m)?;
+    let mut b = b.checked_rem_euclid(m)?;
+    if let Some(product) = a.checked_mul(b) {
+        return product.checked_rem_euclid(m);
+    }
+    let mut result = T::ZERO;
+    while b > T::ZERO {
+        if b.checked_rem(T::TWO)? == T::ONE {
+            result = add_mod(result, a, m)?;
+        }
+        a = add_mod(a, a, m)?;
+        b = b.checked_div(T::TWO)?;
+    }
+    Some(result)
+}
+
+/// Returns `base` to the power of `exponent` modulo `m` in the range `0..m`.
+///
+/// Returns `None` if the exponent is negative or the modulus is not positive.
+#[must_use]
+pub fn mod_pow<T: Integer>(base: T, mut exponent: T, m: T) -> Option<T> {
+    if exponent < T::ZERO || m <= T::ZERO {
+        return None;
+    }
+    let mut base = base.checked_rem_euclid(m)?;
+    let mut result = T::ONE.checked_rem_euclid(m)?;
+    while exponent > T::ZERO {
+        if exponent.checked_rem(T::TWO)? == T::ONE {
+            result = mul_mod(result, base, m)?;
+        }
+        base = mul_mod(base, base, m)?;
+        exponent = exponent.checked_div(T::TWO)?;
+    }
+    Some(result)
+}
+
+/// Solves a system of congruences `x ≡ residue (mod modulus)` using the Chinese remainder theorem.
+///
+/// The moduli do not need to be coprime. Returns `(x, m)` where `x` is the smallest non-negative
+/// solution and `m` is the lcm of the moduli, so that every solution is `x + k * m`.
+/// Returns `None` if the system has no solution, a modulus is not positive, or the lcm overflows.
+#[must_use]
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut x = T::ZERO;
+    let mut m = T::ONE;
+    for &(residue, modulus) in congruences {
+        if modulus <= T::ZERO {
+            return None;
+        }
+        let residue = residue.checked_rem_euclid(modulus)?;
+        let g = gcd(m, modulus)?;
+        let difference = residue.checked_sub(x.checked_rem_euclid(modulus)?)?;
+        if difference.checked_rem(g)? != T::ZERO {
+            return None;
+        }
+        // x + m * t ≡ residue (mod modulus)  <=>  (m / g) * t ≡ difference / g (mod modulus / g)
+        let reduced = modulus.checked_div(g)?;
+        let inverse = mod_inverse(m.checked_div(g)?, reduced)?;
+        let t = mul_mod(difference.checked_div(g)?, inverse, reduced)?;
+        let lcm = m.checked_mul(reduced)?;
+        // x < m and m * t < lcm, so the sum below fits whenever the lcm does
+        x = add_mod(x, m.checked_mul(t)?, lcm)?;
+        m = lcm;
+    }
+    Some((x, m))
+}
+
+/// Returns the integer square root of `n`, rounded down, or `None` if `n` is negative.
+#[must_use]
+pub fn isqrt<T: Integer>(n: T) -> Option<T> {
+    if n < T::ZERO {
+        return None;
+    }
+    if n < T::TWO {
+        return Some(n);
+    }
+    let half = |v: T| v.checked_div(T::TWO);
+    let odd = |v: T| v.checked_rem(T::TWO);
+    // Newton's method from above; the average is computed without overflowing
+    let mut x = half(n)?.checked_add(T::ONE)?;
+    loop {
+        let q = n.checked_div(x)?;
+        let y = half(x)?
+            .checked_add(half(q)?)?
+            .checked_add(half(odd(x)?.checked_add(odd(q)?)?)?)?;
+        if y >= x {
+            return Some(x);
+        }
+        x = y;
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn gcd_lcm() {
+        assert_eq!(gcd(12isize, 18), Some(6));
+        assert_eq!(gcd(-12isize, 18), Some(6));
+        assert_eq!(gcd(0isize, 0), Some(0));
+        assert_eq!(gcd(isize::MIN, 2), Some(2));
+        assert_eq!(gcd(isize::MIN, 0), None);
+        assert_eq!(lcm(4isize, 6), Some(12));
+        assert_eq!(lcm(-4i128, 6), Some(12));
+        assert_eq!(lcm(0isize, 6), Some(0));
+        assert_eq!(lcm(isize::MAX, isize::MAX - 1), None);
+    }
+
+    #[test]
+    fn extended() {
+        for (a, b) in [(240isize, 46), (-240, 46), (17, 0), (0, -5), (101, 103)] {
+            let (g, x, y) = extended_gcd(a, b).expect("No overflow");
+            assert_eq!(Some(g), gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(mod_inverse(3isize, 11), Some(4));
+        assert_eq!(mod_inverse(-3isize, 11), Some(7));
+        assert_eq!(mod_inverse(6isize, 9), None);
+        assert_eq!(mod_inverse(3isize, 0), None);
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(mod_pow(4isize, 13, 497), Some(445));
+        assert_eq!(mod_pow(2isize, 0, 1), Some(0));
+        assert_eq!(mod_pow(2isize, -1, 7), None);
+        let m = i128::MAX - 1;
+        assert_eq!(mul_mod(m - 1, m - 1, m), Some(1));
+        assert_eq!(mod_pow(m - 1, 3, m), Some(m - 1));
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt(&[(2isize, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Non-coprime moduli
+        assert_eq!(crt(&[(2isize, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1isize, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(-1i128, 101), (-1, 103)]), Some((10402, 10403)));
+        assert_eq!(crt::<isize>(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn square_root() {
+        for n in 0isize..1000 {
+            let root = isqrt(n).expect("Non-negative");
+            assert!(root * root <= n && (root + 1) * (root + 1) > n, "{n}");
+        }
+        assert_eq!(isqrt(-1isize), None);
+        assert_eq!(isqrt(i128::MAX), Some(13_043_817_825_332_782_212));
+        assert_eq!(isqrt(isize::MAX), Some(3_037_000_499));
+    }
+}
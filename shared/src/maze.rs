@@ -1,12 +1,13 @@
 use std::{
     collections::HashMap,
+    fmt::Display,
     str::FromStr,
     sync::{Arc, Mutex},
 };
 
 use nalgebra::Point2;
 
-use crate::Coordinate;
+use crate::{Coordinate, Displacement};
 
 #[derive(Clone, Default)]
 pub struct Maze {
@@ -17,24 +18,73 @@ impl FromStr for Maze {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Maze::parse_at(s, Coordinate::origin())
+    }
+}
+
+impl Maze {
+    /// Parses a maze whose top left character is placed at the given `origin`,
+    /// which may have negative components.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a coordinate does not fit in an `isize`.
+    pub fn parse_at(s: &str, origin: Coordinate) -> Result<Self, String> {
+        let e = "Maze coordinate out of range";
         let mut maze = HashMap::new();
         for (row, contents) in s.lines().enumerate() {
+            let y = isize::try_from(row)
+                .ok()
+                .and_then(|row| origin.y.checked_add(row))
+                .ok_or(e)?;
             for (column, character) in contents.chars().enumerate() {
-                // FIXME: ugly casts
-                #[allow(clippy::cast_possible_wrap)]
-                let coordinate = Coordinate::new(
-                    column.try_into().expect("Number conversion error"),
-                    row.try_into().expect("Number conversion error"),
-                );
-                maze.insert(coordinate, character);
+                let x = isize::try_from(column)
+                    .ok()
+                    .and_then(|column| origin.x.checked_add(column))
+                    .ok_or(e)?;
+                maze.insert(Coordinate::new(x, y), character);
             }
         }
 
         Ok(Maze { maze })
     }
-}
 
-impl Maze {
+    /// Returns the top left and bottom right corners of the bounding box of this [`Maze`],
+    /// or `None` if the maze is empty.
+    #[must_use]
+    pub fn bounds(&self) -> Option<(Coordinate, Coordinate)> {
+        let mut coordinates = self.maze.keys();
+        let &first = coordinates.next()?;
+        Some(coordinates.fold((first, first), |(min, max), c| (min.inf(c), max.sup(c))))
+    }
+
+    /// Returns a copy of this [`Maze`] with every coordinate moved by the given displacement.
+    #[must_use]
+    pub fn translate(&self, displacement: Displacement) -> Self {
+        let maze = self
+            .maze
+            .iter()
+            .map(|(&coordinate, &character)| (coordinate + displacement, character))
+            .collect();
+        Maze { maze }
+    }
+
+    /// Renders the bounding box of this [`Maze`] row by row, using `fill` for missing cells.
+    #[must_use]
+    pub fn render(&self, fill: char) -> String {
+        let mut string = String::new();
+        let Some((min, max)) = self.bounds() else {
+            return string;
+        };
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                string.push(self.get(Coordinate::new(x, y)).unwrap_or(fill));
+            }
+            string.push('\n');
+        }
+        string
+    }
+
     #[must_use]
     pub fn all_coordinates(&self) -> Vec<Coordinate> {
         self.maze.keys().copied().collect()
@@ -88,3 +138,37 @@ impl Maze {
         Arc::new(Mutex::new(self))
     }
 }
+
+impl Display for Maze {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(' '))
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+
+    #[test]
+    fn parse_at_negative_origin() {
+        let maze = Maze::parse_at("ab\ncd", Coordinate::new(-1, -1)).expect("Unable to parse maze");
+        assert_eq!(maze.get(Coordinate::new(-1, -1)), Some('a'));
+        assert_eq!(maze.get(Coordinate::new(0, 0)), Some('d'));
+        assert_eq!(
+            maze.bounds(),
+            Some((Coordinate::new(-1, -1), Coordinate::new(0, 0)))
+        );
+    }
+
+    #[test]
+    fn render_sparse() {
+        let mut maze = Maze::default();
+        assert_eq!(maze.bounds(), None);
+        maze.upsert(Coordinate::new(-2, -1), '#');
+        maze.upsert(Coordinate::new(1, 0), '@');
+        assert_eq!(maze.render('.'), "#...\n...@\n");
+        let moved = maze.translate(Displacement::new(2, 1));
+        assert_eq!(moved.get(Coordinate::new(0, 0)), Some('#'));
+        assert_eq!(moved.render('.'), maze.render('.'));
+    }
+}
@@ -0,0 +1,945 @@
+//! Generic shortest path searches.
+//!
+//! The searches are written over an arbitrary state type, so a state can be a plain [`Coordinate`](crate::Coordinate)
+//! or carry extra information such as the current heading. The graph is given as a `neighbours`
+//! closure returning the reachable states with their step costs, which typically captures a [`Maze`](crate::Maze).
+
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use num_bigint::BigUint;
+
+use crate::{geometry, Coordinate, Direction};
+
+/// A path found by a search, from the start state to a goal state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<S> {
+    /// Total cost of the path
+    pub cost: usize,
+    /// States along the path, including the start and the goal
+    pub states: Vec<S>,
+}
+
+/// Index of a node in a [`SearchTree`].
+pub type NodeId = usize;
+
+#[derive(Clone, Debug)]
+struct Node<S> {
+    state: S,
+    parent: Option<NodeId>,
+    cost: usize,
+}
+
+/// An arena of search nodes, each linked to the node it was reached from.
+///
+/// Nodes are never cloned along with their ancestors, so recording a step costs the same
+/// regardless of the path length, and paths are reconstructed iteratively.
+#[derive(Clone, Debug)]
+pub struct SearchTree<S> {
+    nodes: Vec<Node<S>>,
+}
+
+impl<S> Default for SearchTree<S> {
+    fn default() -> Self {
+        SearchTree { nodes: Vec::new() }
+    }
+}
+
+impl<S: Clone> SearchTree<S> {
+    #[must_use]
+    pub fn new() -> Self {
+        SearchTree::default()
+    }
+
+    /// Adds a node reached from `parent` at the given total cost, and returns its id.
+    pub fn push(&mut self, state: S, parent: Option<NodeId>, cost: usize) -> NodeId {
+        self.nodes.push(Node {
+            state,
+            parent,
+            cost,
+        });
+        self.nodes.len() - 1
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    #[must_use]
+    pub fn state(&self, id: NodeId) -> &S {
+        &self.nodes[id].state
+    }
+
+    #[must_use]
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    #[must_use]
+    pub fn cost(&self, id: NodeId) -> usize {
+        self.nodes[id].cost
+    }
+
+    /// Returns the number of steps from the root to the given node.
+    #[must_use]
+    pub fn depth(&self, id: NodeId) -> usize {
+        self.ancestors(id).count() - 1
+    }
+
+    /// Iterates the node itself and then its ancestors, up to the root.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(Some(id), |&id| self.parent(id))
+    }
+
+    /// Returns the states from the root to the given node.
+    #[must_use]
+    pub fn path(&self, id: NodeId) -> Vec<S> {
+        let mut states: Vec<S> = self
+            .ancestors(id)
+            .map(|id| self.state(id).clone())
+            .collect();
+        states.reverse();
+        states
+    }
+
+    /// Returns the path from the root to the given node, with its cost.
+    #[must_use]
+    pub fn to_path(&self, id: NodeId) -> Path<S> {
+        Path {
+            cost: self.cost(id),
+            states: self.path(id),
+        }
+    }
+}
+
+/// A finished search, with every node it recorded on the way.
+#[derive(Clone, Debug)]
+pub struct Search<S> {
+    pub tree: SearchTree<S>,
+    /// Node of the goal the search ended at, or `None` if no goal was reached
+    pub goal: Option<NodeId>,
+}
+
+impl<S: Clone> Search<S> {
+    /// Returns the path from the start to the goal, if one was reached.
+    #[must_use]
+    pub fn path(&self) -> Option<Path<S>> {
+        Some(self.tree.to_path(self.goal?))
+    }
+}
+
+/// Finds the path with the fewest steps from `start` to any state satisfying `is_goal`, using breadth-first search.
+pub fn bfs<S, N, I, G>(start: S, neighbours: N, is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    bfs_search(start, neighbours, is_goal).path()
+}
+
+/// Runs the search of [`bfs`], and returns it with its tree of visited states.
+pub fn bfs_search<S, N, I, G>(start: S, mut neighbours: N, mut is_goal: G) -> Search<S>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut tree = SearchTree::new();
+    let mut visited: HashSet<S> = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    visited.insert(start.clone());
+    queue.push_back(tree.push(start, None, 0));
+
+    while let Some(id) = queue.pop_front() {
+        let state = tree.state(id).clone();
+        if is_goal(&state) {
+            return Search {
+                tree,
+                goal: Some(id),
+            };
+        }
+        let cost = tree.cost(id) + 1;
+        for next in neighbours(&state) {
+            if visited.insert(next.clone()) {
+                queue.push_back(tree.push(next, Some(id), cost));
+            }
+        }
+    }
+
+    Search { tree, goal: None }
+}
+
+/// Finds the path with the fewest steps from `start` to `goal`, using breadth-first search from both ends.
+///
+/// Each round expands a whole level of the smaller frontier, and the search stops as soon as the two
+/// searches meet. The `neighbours` must be symmetric, i.e. `b` is a neighbour of `a` exactly when `a` is
+/// a neighbour of `b`, since the backward search walks the same edges in reverse.
+pub fn bidirectional_bfs<S, N, I>(start: S, goal: S, mut neighbours: N) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    struct Side<S> {
+        tree: SearchTree<S>,
+        seen: HashMap<S, NodeId>,
+        frontier: Vec<NodeId>,
+    }
+
+    let side = |root: S| {
+        let mut tree = SearchTree::new();
+        let id = tree.push(root.clone(), None, 0);
+        Side {
+            tree,
+            seen: HashMap::from([(root, id)]),
+            frontier: vec![id],
+        }
+    };
+    if start == goal {
+        return Some(Path {
+            cost: 0,
+            states: vec![start],
+        });
+    }
+    let mut forward = side(start);
+    let mut backward = side(goal);
+
+    while !forward.frontier.is_empty() && !backward.frontier.is_empty() {
+        let forward_turn = forward.frontier.len() <= backward.frontier.len();
+        let (this, other) = match forward_turn {
+            true => (&mut forward, &mut backward),
+            false => (&mut backward, &mut forward),
+        };
+        // Every meeting found within the first level that meets is equally short, so the first one will do
+        let mut meeting = None;
+        let mut next = Vec::new();
+        'level: for id in std::mem::take(&mut this.frontier) {
+            let state = this.tree.state(id).clone();
+            let cost = this.tree.cost(id) + 1;
+            for neighbour in neighbours(&state) {
+                if this.seen.contains_key(&neighbour) {
+                    continue;
+                }
+                let child = this.tree.push(neighbour.clone(), Some(id), cost);
+                if let Some(&other_id) = other.seen.get(&neighbour) {
+                    meeting = Some((child, other_id));
+                    break 'level;
+                }
+                this.seen.insert(neighbour, child);
+                next.push(child);
+            }
+        }
+        this.frontier = next;
+
+        if let Some((this_id, other_id)) = meeting {
+            let (forward_id, backward_id) = match forward_turn {
+                true => (this_id, other_id),
+                false => (other_id, this_id),
+            };
+            let mut states = forward.tree.path(forward_id);
+            let tail = backward.tree.ancestors(backward_id).skip(1);
+            states.extend(tail.map(|id| backward.tree.state(id).clone()));
+            return Some(Path {
+                cost: forward.tree.cost(forward_id) + backward.tree.cost(backward_id),
+                states,
+            });
+        }
+    }
+
+    None
+}
+
+/// Finds the path with the fewest steps from `start` to `goal` through the open cells of a grid, moving
+/// `N`, `W`, `E` or `S`, using jump point search.
+///
+/// Among equally short paths only the canonical ones are searched, which make their horizontal moves
+/// before their vertical ones unless an obstacle forces a turn. Straight runs without such forced turns
+/// are skipped over in one go, so open areas expand only a few jump points instead of every cell.
+/// The returned path still lists every cell along the way.
+///
+/// The open cells must form a finite area, e.g. by treating cells outside the grid as blocked.
+pub fn jump_point_search<O>(
+    start: Coordinate,
+    goal: Coordinate,
+    is_open: O,
+) -> Option<Path<Coordinate>>
+where
+    O: Fn(Coordinate) -> bool,
+{
+    if !is_open(start) || !is_open(goal) {
+        return None;
+    }
+    let is_horizontal = |direction: Direction| direction.to_displacement().y == 0;
+    // A vertical move forces a turn to the side when the side cell is open, but could not have been
+    // reached canonically by moving to the side first and then vertically.
+    let forced = |at: Coordinate, heading: Direction, side: Direction| {
+        let side = side.to_displacement();
+        is_open(at + side) && !is_open(at - heading.to_displacement() + side)
+    };
+    // Every cell of a horizontal jump starts two vertical jumps, so their results are remembered for
+    // every cell passed on the way. Each cell is then scanned at most once per vertical heading.
+    let vertical_jumps: RefCell<HashMap<(Coordinate, Direction), Option<Coordinate>>> =
+        RefCell::default();
+    let jump_vertical = |from: Coordinate, heading: Direction| {
+        let mut passed = Vec::new();
+        let mut at = from;
+        let target = loop {
+            if let Some(&known) = vertical_jumps.borrow().get(&(at, heading)) {
+                break known;
+            }
+            passed.push(at);
+            at += heading.to_displacement();
+            if !is_open(at) {
+                break None;
+            }
+            if at == goal || forced(at, heading, Direction::W) || forced(at, heading, Direction::E)
+            {
+                break Some(at);
+            }
+        };
+        let mut known = vertical_jumps.borrow_mut();
+        known.extend(passed.into_iter().map(|cell| ((cell, heading), target)));
+        target
+    };
+    let jump = |mut at: Coordinate, heading: Direction| {
+        if !is_horizontal(heading) {
+            return jump_vertical(at, heading);
+        }
+        // Vertical moves may follow horizontal ones, so a cell is a jump point if a vertical jump from it succeeds
+        loop {
+            at += heading.to_displacement();
+            if !is_open(at) {
+                return None;
+            }
+            if at == goal
+                || jump_vertical(at, Direction::N).is_some()
+                || jump_vertical(at, Direction::S).is_some()
+            {
+                return Some(at);
+            }
+        }
+    };
+
+    let path = astar(
+        (start, None),
+        |&(at, heading): &(Coordinate, Option<Direction>)| {
+            let headings: Vec<Direction> = match heading {
+                None => Direction::cardinals().collect(),
+                Some(heading) if is_horizontal(heading) => {
+                    vec![heading, Direction::N, Direction::S]
+                }
+                Some(heading) => std::iter::once(heading)
+                    .chain(
+                        [Direction::W, Direction::E]
+                            .into_iter()
+                            .filter(|&side| forced(at, heading, side)),
+                    )
+                    .collect(),
+            };
+            headings
+                .into_iter()
+                .filter_map(|heading| {
+                    let next = jump(at, heading)?;
+                    Some(((next, Some(heading)), geometry::manhattan(at, next)))
+                })
+                .collect::<Vec<_>>()
+        },
+        |&(at, _)| geometry::manhattan(at, goal),
+        |&(at, _)| at == goal,
+    )?;
+
+    let mut states = vec![start];
+    for pair in path.states.windows(2) {
+        states.extend(geometry::segment(pair[0].0, pair[1].0).into_iter().skip(1));
+    }
+    Some(Path {
+        cost: path.cost,
+        states,
+    })
+}
+
+/// Finds the cheapest path from `start` to any state satisfying `is_goal`, using Dijkstra's algorithm.
+pub fn dijkstra<S, N, I, G>(start: S, neighbours: N, is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    G: FnMut(&S) -> bool,
+{
+    astar(start, neighbours, |_| 0, is_goal)
+}
+
+/// Finds the cheapest path from `start` to any state satisfying `is_goal`, using the A* algorithm.
+///
+/// The `heuristic` must never overestimate the remaining cost to the nearest goal,
+/// otherwise the returned path may not be the cheapest one.
+pub fn astar<S, N, I, H, G>(start: S, neighbours: N, heuristic: H, is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    H: FnMut(&S) -> usize,
+    G: FnMut(&S) -> bool,
+{
+    astar_search(start, neighbours, heuristic, is_goal).path()
+}
+
+/// Runs the search of [`astar`], and returns it with its tree of every route queued along the way.
+pub fn astar_search<S, N, I, H, G>(
+    start: S,
+    mut neighbours: N,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> Search<S>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    H: FnMut(&S) -> usize,
+    G: FnMut(&S) -> bool,
+{
+    let mut costs: HashMap<S, usize> = HashMap::new();
+    // The heap holds node ids, so that `S` does not need to be `Ord`
+    let mut tree = SearchTree::new();
+    let mut queue = BinaryHeap::new();
+
+    costs.insert(start.clone(), 0);
+    let estimate = heuristic(&start);
+    queue.push(Reverse((estimate, 0, tree.push(start, None, 0))));
+
+    while let Some(Reverse((_, cost, id))) = queue.pop() {
+        let state = tree.state(id).clone();
+        if costs.get(&state).is_some_and(|&best| best < cost) {
+            // A cheaper route to this state has already been expanded
+            continue;
+        }
+        if is_goal(&state) {
+            return Search {
+                tree,
+                goal: Some(id),
+            };
+        }
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost.saturating_add(step);
+            if costs.get(&next).is_some_and(|&best| best <= next_cost) {
+                continue;
+            }
+            costs.insert(next.clone(), next_cost);
+            let estimate = next_cost.saturating_add(heuristic(&next));
+            queue.push(Reverse((
+                estimate,
+                next_cost,
+                tree.push(next, Some(id), next_cost),
+            )));
+        }
+    }
+
+    Search { tree, goal: None }
+}
+
+/// Explores every state reachable from `start` using Dijkstra's algorithm, and returns the tree of cheapest routes.
+///
+/// Each reachable state appears in the tree exactly once, linked to the state it is cheapest reached from,
+/// in the order the states were settled.
+pub fn explore<S, N, I>(start: S, mut neighbours: N) -> SearchTree<S>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let mut tree = SearchTree::new();
+    let mut costs: HashMap<S, usize> = HashMap::new();
+    let mut settled: HashSet<S> = HashSet::new();
+    // The heap holds indices into the candidates, so that `S` does not need to be `Ord`
+    let mut candidates: Vec<(S, Option<NodeId>)> = Vec::new();
+    let mut queue = BinaryHeap::new();
+
+    costs.insert(start.clone(), 0);
+    candidates.push((start, None));
+    queue.push(Reverse((0, 0)));
+
+    while let Some(Reverse((cost, index))) = queue.pop() {
+        let (state, parent) = candidates[index].clone();
+        if !settled.insert(state.clone()) {
+            continue;
+        }
+        let id = tree.push(state.clone(), parent, cost);
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost.saturating_add(step);
+            if settled.contains(&next) || costs.get(&next).is_some_and(|&best| best <= next_cost) {
+                continue;
+            }
+            costs.insert(next.clone(), next_cost);
+            queue.push(Reverse((next_cost, candidates.len())));
+            candidates.push((next, Some(id)));
+        }
+    }
+
+    tree
+}
+
+/// Every cheapest path from a start state to the goal states, as a DAG of predecessor links.
+#[derive(Clone, Debug)]
+pub struct ShortestPaths<S> {
+    /// Cost of the cheapest paths
+    pub cost: usize,
+    /// Goal states reachable at the cheapest cost
+    pub goals: Vec<S>,
+    start: S,
+    predecessors: HashMap<S, Vec<S>>,
+}
+
+impl<S: Clone + Eq + Hash> ShortestPaths<S> {
+    /// Returns the states preceding the given state on some cheapest path.
+    #[must_use]
+    pub fn predecessors(&self, state: &S) -> &[S] {
+        self.predecessors.get(state).map_or(&[], Vec::as_slice)
+    }
+
+    /// Counts the distinct cheapest paths to any of the goals.
+    ///
+    /// The count can grow exponentially with the path length, so it is an arbitrary-precision integer.
+    #[must_use]
+    pub fn count_paths(&self) -> BigUint {
+        // Counts are computed in a depth-first post-order of the predecessor links, rather than the order the
+        // states were settled in, because a zero-cost step can link a state to a predecessor settled after it
+        let mut counts: HashMap<&S, BigUint> = HashMap::new();
+        let mut stack: Vec<(&S, bool)> = self.goals.iter().map(|goal| (goal, false)).collect();
+        while let Some((state, expanded)) = stack.pop() {
+            if counts.contains_key(state) {
+                continue;
+            }
+            if *state == self.start {
+                counts.insert(state, BigUint::from(1u8));
+                continue;
+            }
+            if !expanded {
+                stack.push((state, true));
+                stack.extend(self.predecessors(state).iter().map(|p| (p, false)));
+                continue;
+            }
+            let count = self
+                .predecessors(state)
+                .iter()
+                .filter_map(|predecessor| counts.get(predecessor))
+                .sum();
+            counts.insert(state, count);
+        }
+        self.goals.iter().filter_map(|goal| counts.get(goal)).sum()
+    }
+
+    /// Returns every state that lies on at least one cheapest path.
+    #[must_use]
+    pub fn states(&self) -> HashSet<S> {
+        let mut states: HashSet<S> = self.goals.iter().cloned().collect();
+        let mut stack = self.goals.clone();
+        while let Some(state) = stack.pop() {
+            for predecessor in self.predecessors(&state) {
+                if states.insert(predecessor.clone()) {
+                    stack.push(predecessor.clone());
+                }
+            }
+        }
+        states
+    }
+
+    /// Lazily enumerates every cheapest path, each from the start state to a goal.
+    pub fn paths(&self) -> impl Iterator<Item = Vec<S>> + '_ {
+        let mut stack: Vec<Vec<S>> = self.goals.iter().map(|goal| vec![goal.clone()]).collect();
+        std::iter::from_fn(move || {
+            while let Some(partial) = stack.pop() {
+                let last = partial.last()?;
+                if *last == self.start {
+                    let mut path = partial;
+                    path.reverse();
+                    return Some(path);
+                }
+                for predecessor in self.predecessors(last).iter().rev() {
+                    let mut extended = partial.clone();
+                    extended.push(predecessor.clone());
+                    stack.push(extended);
+                }
+            }
+            None
+        })
+    }
+}
+
+/// Finds every cheapest path from `start` to the states satisfying `is_goal`, using Dijkstra's algorithm.
+///
+/// Goal states are not expanded further. Steps may cost zero, as long as they do not form a cycle.
+pub fn dijkstra_all<S, N, I, G>(
+    start: S,
+    mut neighbours: N,
+    mut is_goal: G,
+) -> Option<ShortestPaths<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    G: FnMut(&S) -> bool,
+{
+    let mut costs: HashMap<S, usize> = HashMap::new();
+    let mut predecessors: HashMap<S, Vec<S>> = HashMap::new();
+    let mut settled: HashSet<S> = HashSet::new();
+    let mut goals: Vec<S> = Vec::new();
+    let mut best: Option<usize> = None;
+    let mut states: Vec<S> = Vec::new();
+    let mut queue = BinaryHeap::new();
+
+    queue.push(Reverse((0, 0)));
+    costs.insert(start.clone(), 0);
+    states.push(start.clone());
+
+    while let Some(Reverse((cost, index))) = queue.pop() {
+        if best.is_some_and(|best| cost > best) {
+            break;
+        }
+        let state = states[index].clone();
+        if costs.get(&state).is_some_and(|&known| known < cost) || !settled.insert(state.clone()) {
+            continue;
+        }
+        if is_goal(&state) {
+            best = Some(cost);
+            goals.push(state);
+            continue;
+        }
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost.saturating_add(step);
+            match costs.get(&next) {
+                Some(&known) if known < next_cost => continue,
+                Some(&known) if known == next_cost => {
+                    let links = predecessors.entry(next).or_default();
+                    if !links.contains(&state) {
+                        links.push(state.clone());
+                    }
+                    continue;
+                }
+                _ => (),
+            }
+            costs.insert(next.clone(), next_cost);
+            predecessors.insert(next.clone(), vec![state.clone()]);
+            queue.push(Reverse((next_cost, states.len())));
+            states.push(next);
+        }
+    }
+
+    Some(ShortestPaths {
+        cost: best?,
+        goals,
+        start,
+        predecessors,
+    })
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use crate::{geometry, Coordinate, Direction, Maze};
+
+    const MAZE: &str = "\
+S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E";
+
+    fn open_neighbours(maze: &Maze, coordinate: Coordinate) -> Vec<(Coordinate, usize)> {
+        Direction::cardinals()
+            .map(|d| coordinate + d.to_displacement())
+            .filter(|&next| maze.get(next).is_some_and(|c| c != '#'))
+            .map(|next| (next, 1))
+            .collect()
+    }
+
+    #[test]
+    fn grid_dijkstra() {
+        let maze: Maze = MAZE.parse().expect("Unable to parse maze");
+        let start = maze.find('S').expect("No start");
+        let end = maze.find('E').expect("No end");
+        let path =
+            dijkstra(start, |&c| open_neighbours(&maze, c), |&c| c == end).expect("No path found");
+        assert_eq!(path.cost, 15);
+        assert_eq!(path.states.len(), 16);
+        assert_eq!(path.states.first(), Some(&start));
+        assert_eq!(path.states.last(), Some(&end));
+        assert!(path
+            .states
+            .windows(2)
+            .all(|pair| geometry::manhattan(pair[0], pair[1]) == 1));
+    }
+
+    #[test]
+    fn grid_astar() {
+        let maze: Maze = MAZE.parse().expect("Unable to parse maze");
+        let start = maze.find('S').expect("No start");
+        let end = maze.find('E').expect("No end");
+        let path = astar(
+            start,
+            |&c| open_neighbours(&maze, c),
+            |&c| geometry::manhattan(c, end),
+            |&c| c == end,
+        )
+        .expect("No path found");
+        assert_eq!(path.cost, 15);
+
+        let mut walled = maze.clone();
+        walled.upsert(Coordinate::new(4, 0), '#');
+        walled.upsert(Coordinate::new(4, 2), '#');
+        let path = astar(
+            start,
+            |&c| open_neighbours(&walled, c),
+            |&c| geometry::manhattan(c, end),
+            |&c| c == end,
+        );
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn weighted_states() {
+        // Moving forward costs 1 and turning costs 10, so the search prefers fewer turns
+        let maze: Maze = "...\n...\n...".parse().expect("Unable to parse maze");
+        let start = (Coordinate::new(0, 0), Direction::E);
+        let end = Coordinate::new(2, 2);
+        let path = dijkstra(
+            start,
+            |&(coordinate, heading)| {
+                let forward = coordinate + heading.to_displacement();
+                let mut next = vec![
+                    ((coordinate, heading.rotate_cw()), 10),
+                    ((coordinate, heading.rotate_ccw()), 10),
+                ];
+                if maze.contains_coordinate(forward) {
+                    next.push(((forward, heading), 1));
+                }
+                next
+            },
+            |&(coordinate, _)| coordinate == end,
+        )
+        .expect("No path found");
+        assert_eq!(path.cost, 14);
+    }
+
+    #[test]
+    fn start_is_goal() {
+        let path = dijkstra(0, |&n| [(n + 1, 1)], |&n| n == 0).expect("No path found");
+        assert_eq!(
+            path,
+            Path {
+                cost: 0,
+                states: vec![0]
+            }
+        );
+    }
+
+    #[test]
+    fn all_shortest_paths() {
+        // Three rows of three cells, moving only east or south: six paths corner to corner
+        let maze: Maze = "...\n...\n...".parse().expect("Unable to parse maze");
+        let end = Coordinate::new(2, 2);
+        let paths = dijkstra_all(
+            Coordinate::new(0, 0),
+            |&c| {
+                [Direction::E, Direction::S]
+                    .into_iter()
+                    .map(move |d| c + d.to_displacement())
+                    .filter(|&next| maze.contains_coordinate(next))
+                    .map(|next| (next, 1))
+                    .collect::<Vec<_>>()
+            },
+            |&c| c == end,
+        )
+        .expect("No path found");
+        assert_eq!(paths.cost, 4);
+        assert_eq!(paths.count_paths(), BigUint::from(6u8));
+        assert_eq!(paths.states().len(), 9);
+        let all: HashSet<Vec<Coordinate>> = paths.paths().collect();
+        assert_eq!(all.len(), 6);
+        assert!(all.iter().all(|path| path.len() == 5));
+    }
+
+    #[test]
+    fn only_cheapest_paths() {
+        let maze: Maze = MAZE.parse().expect("Unable to parse maze");
+        let start = maze.find('S').expect("No start");
+        let end = maze.find('E').expect("No end");
+        let paths = dijkstra_all(start, |&c| open_neighbours(&maze, c), |&c| c == end)
+            .expect("No path found");
+        assert_eq!(paths.cost, 15);
+        assert_eq!(paths.goals, [end]);
+        assert_eq!(paths.count_paths(), BigUint::from(1u8));
+        let path: Vec<Vec<Coordinate>> = paths.paths().collect();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].len(), 16);
+        assert_eq!(paths.states().len(), 16);
+    }
+
+    #[test]
+    fn zero_cost_predecessor() {
+        // X is settled before Y, and only then linked to it by the free step from Y
+        let graph = |&state: &char| match state {
+            'S' => vec![('X', 1), ('Y', 1)],
+            'Y' => vec![('X', 0)],
+            _ => vec![],
+        };
+        let paths = dijkstra_all('S', graph, |&state| state == 'X').expect("No path found");
+        assert_eq!(paths.cost, 1);
+        assert_eq!(paths.count_paths(), BigUint::from(2u8));
+        assert_eq!(paths.paths().count(), 2);
+    }
+
+    #[test]
+    fn count_beyond_u128() {
+        // A chain of diamonds doubles the number of paths at each step
+        let diamonds = |&(n, _): &(usize, bool)| {
+            if n >= 200 {
+                return vec![];
+            }
+            vec![((n + 1, false), 1), ((n + 1, true), 1)]
+        };
+        let paths = dijkstra_all((0, false), diamonds, |&(n, _)| n == 100).expect("No path found");
+        assert_eq!(paths.count_paths(), BigUint::from(1u8) << 100);
+        let paths = dijkstra_all((0, false), diamonds, |&(n, _)| n == 200).expect("No path found");
+        assert_eq!(paths.count_paths(), BigUint::from(1u8) << 200);
+        assert_eq!(paths.paths().take(3).count(), 3);
+    }
+
+    #[test]
+    fn grid_bfs() {
+        let maze: Maze = MAZE.parse().expect("Unable to parse maze");
+        let start = maze.find('S').expect("No start");
+        let end = maze.find('E').expect("No end");
+        let neighbours = |&c: &Coordinate| open_neighbours(&maze, c).into_iter().map(|(n, _)| n);
+        let path = bfs(start, neighbours, |&c| c == end).expect("No path found");
+        let expected = dijkstra(start, |&c| open_neighbours(&maze, c), |&c| c == end);
+        assert_eq!(Some(path), expected);
+    }
+
+    #[test]
+    fn search_tree() {
+        let mut tree = SearchTree::new();
+        let root = tree.push('a', None, 0);
+        let child = tree.push('b', Some(root), 1);
+        let sibling = tree.push('c', Some(root), 5);
+        let leaf = tree.push('d', Some(child), 2);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.depth(root), 0);
+        assert_eq!(tree.depth(leaf), 2);
+        assert_eq!(tree.path(leaf), ['a', 'b', 'd']);
+        assert_eq!(tree.path(sibling), ['a', 'c']);
+        assert_eq!(tree.to_path(leaf).cost, 2);
+    }
+
+    /// Builds a pseudo-random grid with walls on roughly a third of the cells, using a linear congruential generator.
+    fn random_maze(seed: u64, width: isize, height: isize) -> Maze {
+        let mut state = seed;
+        let mut maze = Maze::default();
+        for y in 0..height {
+            for x in 0..width {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                let tile = match (state >> 33) % 3 {
+                    0 => '#',
+                    _ => '.',
+                };
+                maze.upsert(Coordinate::new(x, y), tile);
+            }
+        }
+        maze
+    }
+
+    fn assert_valid(maze: &Maze, path: &Path<Coordinate>, start: Coordinate, end: Coordinate) {
+        assert_eq!(path.states.first(), Some(&start));
+        assert_eq!(path.states.last(), Some(&end));
+        assert_eq!(path.states.len(), path.cost + 1);
+        assert!(path.states.iter().all(|&c| maze.get(c) == Some('.')));
+        assert!(path
+            .states
+            .windows(2)
+            .all(|pair| geometry::manhattan(pair[0], pair[1]) == 1));
+    }
+
+    #[test]
+    fn grid_searches_agree() {
+        let (width, height) = (24, 17);
+        let start = Coordinate::new(0, 0);
+        let end = Coordinate::new(width - 1, height - 1);
+        let mut found = 0;
+        for seed in 0..200 {
+            let mut maze = random_maze(seed, width, height);
+            maze.upsert(start, '.');
+            maze.upsert(end, '.');
+            let neighbours =
+                |&c: &Coordinate| open_neighbours(&maze, c).into_iter().map(|(n, _)| n);
+            let expected = bfs(start, neighbours, |&c| c == end);
+            let bidirectional = bidirectional_bfs(start, end, neighbours);
+            let jump = jump_point_search(start, end, |c| maze.get(c) == Some('.'));
+            assert_eq!(
+                expected.as_ref().map(|p| p.cost),
+                bidirectional.as_ref().map(|p| p.cost),
+                "Bidirectional BFS, seed {seed}"
+            );
+            assert_eq!(
+                expected.as_ref().map(|p| p.cost),
+                jump.as_ref().map(|p| p.cost),
+                "Jump point search, seed {seed}"
+            );
+            for path in [bidirectional, jump].iter().flatten() {
+                assert_valid(&maze, path, start, end);
+            }
+            found += usize::from(expected.is_some());
+        }
+        // Both outcomes are covered
+        assert!(found > 20 && found < 180, "{found}");
+    }
+
+    #[test]
+    fn open_grid() {
+        let maze = Maze::parse_at(
+            &format!("{}\n", ".".repeat(50)).repeat(50),
+            Coordinate::new(0, 0),
+        )
+        .expect("Unable to parse maze");
+        let is_open = |c: Coordinate| maze.get(c).is_some();
+        let start = Coordinate::new(0, 0);
+        let end = Coordinate::new(49, 49);
+        let path = jump_point_search(start, end, is_open).expect("No path found");
+        assert_eq!(path.cost, 98);
+        assert_eq!(path.states.len(), 99);
+        assert_eq!(
+            jump_point_search(start, start, is_open).map(|p| p.cost),
+            Some(0)
+        );
+        assert_eq!(
+            bidirectional_bfs(start, start, |&c: &Coordinate| [c]).map(|p| p.cost),
+            Some(0)
+        );
+        assert_eq!(
+            jump_point_search(start, Coordinate::new(0, 50), is_open),
+            None
+        );
+    }
+
+    #[test]
+    fn long_path() {
+        // Deep enough to overflow the stack with recursive reconstruction
+        let path = bfs(0usize, |&n| [n + 1], |&n| n == 200_000).expect("No path found");
+        assert_eq!(path.cost, 200_000);
+        assert_eq!(path.states.len(), 200_001);
+    }
+}
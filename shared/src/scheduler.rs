@@ -0,0 +1,486 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Coordinate, Direction, Maze, Visitor};
+
+/// Something that can be moved around a [`Maze`] by a [`Scheduler`].
+pub trait Agent {
+    /// Returns the current position of the agent.
+    fn position(&self) -> Coordinate;
+
+    /// Returns the coordinate the agent wants to move to on this tick, or `None` to stay put.
+    fn intent(&mut self, maze: &Maze) -> Option<Coordinate>;
+
+    /// Moves the agent to the given coordinate.
+    ///
+    /// Only called with a coordinate the agent returned from [`Agent::intent`] on the same tick.
+    fn advance(&mut self, coordinate: Coordinate);
+
+    /// Priority used by [`Order::Priority`]. Higher priorities move first.
+    fn priority(&self) -> isize {
+        0
+    }
+}
+
+/// The order in which the agents of a [`Scheduler`] are moved within a tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// Agents move one at a time in insertion order, each seeing the moves made before it.
+    #[default]
+    Sequential,
+    /// All agents decide based on the state at the start of the tick and move at once.
+    Simultaneous,
+    /// Like [`Order::Sequential`], but sorted by descending [`Agent::priority`].
+    Priority,
+}
+
+/// How a [`Scheduler`] resolves two agents wanting to occupy the same cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Collision {
+    /// Agents may share cells freely.
+    #[default]
+    Allow,
+    /// An agent that would enter an occupied or contested cell waits in place.
+    /// In simultaneous mode all agents contesting a cell wait.
+    Wait,
+    /// Like [`Collision::Wait`], but in simultaneous mode the first contender in order gets the cell.
+    FirstWins,
+}
+
+/// Summary of a single tick, passed to the hooks of a [`Scheduler`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tick {
+    /// Number of the tick, starting from `1`
+    pub number: usize,
+    /// Number of agents that moved
+    pub moved: usize,
+    /// Number of agents that wanted to move but were blocked by a collision
+    pub blocked: usize,
+}
+
+type Hook<A> = Box<dyn FnMut(&Tick, &Maze, &[A])>;
+
+/// Runs many [`Agent`]s on a shared [`Maze`], tick by tick.
+pub struct Scheduler<A: Agent> {
+    maze: Maze,
+    agents: Vec<A>,
+    order: Order,
+    collision: Collision,
+    ticks: usize,
+    hooks: Vec<Hook<A>>,
+}
+
+impl<A: Agent> Scheduler<A> {
+    #[must_use]
+    pub fn new(maze: Maze, agents: Vec<A>) -> Self {
+        Scheduler {
+            maze,
+            agents,
+            order: Order::default(),
+            collision: Collision::default(),
+            ticks: 0,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Creates a scheduler for agents that move freely, without a maze to constrain them.
+    ///
+    /// The agents and hooks are given an empty maze.
+    #[must_use]
+    pub fn without_maze(agents: Vec<A>) -> Self {
+        Scheduler::new(Maze::default(), agents)
+    }
+
+    #[must_use]
+    pub fn with_order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+
+    #[must_use]
+    pub fn with_collision(mut self, collision: Collision) -> Self {
+        self.collision = collision;
+        self
+    }
+
+    /// Registers a hook that is called at the end of every tick, e.g. for rendering or metrics.
+    pub fn on_tick(&mut self, hook: impl FnMut(&Tick, &Maze, &[A]) + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    #[must_use]
+    pub fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    pub fn maze_mut(&mut self) -> &mut Maze {
+        &mut self.maze
+    }
+
+    #[must_use]
+    pub fn agents(&self) -> &[A] {
+        &self.agents
+    }
+
+    pub fn agents_mut(&mut self) -> &mut [A] {
+        &mut self.agents
+    }
+
+    /// Returns the number of ticks run so far.
+    #[must_use]
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// Moves every agent once, according to the configured [`Order`] and [`Collision`] policy.
+    pub fn tick(&mut self) -> Tick {
+        self.ticks += 1;
+        let mut tick = match self.order {
+            Order::Sequential => self.tick_sequential(&(0..self.agents.len()).collect::<Vec<_>>()),
+            Order::Priority => {
+                let mut indices: Vec<usize> = (0..self.agents.len()).collect();
+                indices.sort_by_key(|&i| std::cmp::Reverse(self.agents[i].priority()));
+                self.tick_sequential(&indices)
+            }
+            Order::Simultaneous => self.tick_simultaneous(),
+        };
+        tick.number = self.ticks;
+
+        for hook in &mut self.hooks {
+            hook(&tick, &self.maze, &self.agents);
+        }
+
+        tick
+    }
+
+    /// Runs the given number of ticks.
+    pub fn run(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.tick();
+        }
+    }
+
+    /// Runs ticks until the predicate holds, checking it before every tick.
+    ///
+    /// Returns the number of ticks run in this call, or `None` if the predicate did not hold within `max_ticks`.
+    pub fn run_until(
+        &mut self,
+        max_ticks: usize,
+        mut predicate: impl FnMut(&Maze, &[A]) -> bool,
+    ) -> Option<usize> {
+        for ticks in 0..=max_ticks {
+            if predicate(&self.maze, &self.agents) {
+                return Some(ticks);
+            }
+            if ticks < max_ticks {
+                self.tick();
+            }
+        }
+        None
+    }
+
+    fn tick_sequential(&mut self, indices: &[usize]) -> Tick {
+        let mut occupied: HashMap<Coordinate, usize> = HashMap::new();
+        if self.collision != Collision::Allow {
+            for agent in &self.agents {
+                *occupied.entry(agent.position()).or_default() += 1;
+            }
+        }
+
+        let mut tick = Tick::default();
+        for &index in indices {
+            let agent = &mut self.agents[index];
+            let position = agent.position();
+            let Some(target) = agent.intent(&self.maze) else {
+                continue;
+            };
+            if target == position {
+                continue;
+            }
+            if self.collision != Collision::Allow {
+                if occupied.get(&target).is_some_and(|&n| n > 0) {
+                    tick.blocked += 1;
+                    continue;
+                }
+                if let Some(n) = occupied.get_mut(&position) {
+                    *n -= 1;
+                }
+                *occupied.entry(target).or_default() += 1;
+            }
+            agent.advance(target);
+            tick.moved += 1;
+        }
+
+        tick
+    }
+
+    fn tick_simultaneous(&mut self) -> Tick {
+        let positions: Vec<Coordinate> = self.agents.iter().map(Agent::position).collect();
+        let mut targets: Vec<Option<Coordinate>> = self
+            .agents
+            .iter_mut()
+            .zip(&positions)
+            .map(|(agent, &position)| agent.intent(&self.maze).filter(|&t| t != position))
+            .collect();
+        let wanted = targets.iter().flatten().count();
+
+        if self.collision != Collision::Allow {
+            self.resolve(&positions, &mut targets);
+        }
+
+        let mut tick = Tick::default();
+        for (agent, target) in self.agents.iter_mut().zip(&targets) {
+            if let Some(target) = *target {
+                agent.advance(target);
+                tick.moved += 1;
+            }
+        }
+        tick.blocked = wanted - tick.moved;
+
+        tick
+    }
+
+    /// Cancels simultaneous moves until no two agents end up in the same cell.
+    fn resolve(&self, positions: &[Coordinate], targets: &mut [Option<Coordinate>]) {
+        loop {
+            let mut changed = false;
+
+            let mut contenders: HashMap<Coordinate, Vec<usize>> = HashMap::new();
+            for (index, target) in targets.iter().enumerate() {
+                if let Some(target) = target {
+                    contenders.entry(*target).or_default().push(index);
+                }
+            }
+            for indices in contenders.values().filter(|indices| indices.len() > 1) {
+                let skip = usize::from(self.collision == Collision::FirstWins);
+                for &index in indices.iter().skip(skip) {
+                    targets[index] = None;
+                    changed = true;
+                }
+            }
+
+            let staying: HashSet<Coordinate> = positions
+                .iter()
+                .zip(targets.iter())
+                .filter(|(_, target)| target.is_none())
+                .map(|(&position, _)| position)
+                .collect();
+            let starts: HashMap<Coordinate, usize> = positions
+                .iter()
+                .enumerate()
+                .map(|(index, &position)| (position, index))
+                .collect();
+            for index in 0..targets.len() {
+                let Some(target) = targets[index] else {
+                    continue;
+                };
+                let swaps = starts
+                    .get(&target)
+                    .is_some_and(|&other| targets[other] == Some(positions[index]));
+                if staying.contains(&target) || swaps {
+                    targets[index] = None;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+/// An [`Agent`] that moves a [`Visitor`] in the direction chosen by a policy closure.
+///
+/// The visitor reads its own shared maze, not the one owned by the [`Scheduler`].
+/// The policy is given both, so it should check the scheduler's maze for anything changed during the run.
+pub struct Guided<F> {
+    visitor: Visitor,
+    policy: F,
+    heading: Option<Direction>,
+}
+
+impl<F> Guided<F>
+where
+    F: FnMut(&Visitor, &Maze) -> Option<Direction>,
+{
+    #[must_use]
+    pub fn new(visitor: Visitor, policy: F) -> Self {
+        Guided {
+            visitor,
+            policy,
+            heading: None,
+        }
+    }
+
+    #[must_use]
+    pub fn visitor(&self) -> &Visitor {
+        &self.visitor
+    }
+}
+
+impl<F> Agent for Guided<F>
+where
+    F: FnMut(&Visitor, &Maze) -> Option<Direction>,
+{
+    fn position(&self) -> Coordinate {
+        self.visitor.position()
+    }
+
+    fn intent(&mut self, maze: &Maze) -> Option<Coordinate> {
+        self.heading = (self.policy)(&self.visitor, maze);
+        self.visitor.coordinate_in_direction(self.heading?)
+    }
+
+    fn advance(&mut self, _coordinate: Coordinate) {
+        if let Some(heading) = self.heading {
+            self.visitor.step(heading);
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::Displacement;
+
+    struct Walker {
+        position: Coordinate,
+        velocity: Displacement,
+        priority: isize,
+    }
+
+    impl Walker {
+        fn new(x: isize, y: isize, dx: isize, dy: isize) -> Self {
+            Walker {
+                position: Coordinate::new(x, y),
+                velocity: Displacement::new(dx, dy),
+                priority: 0,
+            }
+        }
+    }
+
+    impl Agent for Walker {
+        fn position(&self) -> Coordinate {
+            self.position
+        }
+
+        fn intent(&mut self, maze: &Maze) -> Option<Coordinate> {
+            let target = self.position + self.velocity;
+            (maze.get(target) == Some('.')).then_some(target)
+        }
+
+        fn advance(&mut self, coordinate: Coordinate) {
+            self.position = coordinate;
+        }
+
+        fn priority(&self) -> isize {
+            self.priority
+        }
+    }
+
+    fn corridor() -> Maze {
+        ".....".parse().expect("Unable to parse maze")
+    }
+
+    fn positions<A: Agent>(scheduler: &Scheduler<A>) -> Vec<isize> {
+        scheduler.agents().iter().map(|a| a.position().x).collect()
+    }
+
+    #[test]
+    fn allow_shares_cells() {
+        let agents = vec![Walker::new(0, 0, 1, 0), Walker::new(2, 0, -1, 0)];
+        let mut scheduler = Scheduler::new(corridor(), agents).with_order(Order::Simultaneous);
+        scheduler.tick();
+        assert_eq!(positions(&scheduler), [1, 1]);
+    }
+
+    #[test]
+    fn simultaneous_contest() {
+        let agents = || vec![Walker::new(0, 0, 1, 0), Walker::new(2, 0, -1, 0)];
+
+        let mut scheduler = Scheduler::new(corridor(), agents())
+            .with_order(Order::Simultaneous)
+            .with_collision(Collision::Wait);
+        let tick = scheduler.tick();
+        assert_eq!(positions(&scheduler), [0, 2]);
+        assert_eq!((tick.moved, tick.blocked), (0, 2));
+
+        let mut scheduler = Scheduler::new(corridor(), agents())
+            .with_order(Order::Simultaneous)
+            .with_collision(Collision::FirstWins);
+        scheduler.tick();
+        assert_eq!(positions(&scheduler), [1, 2]);
+    }
+
+    #[test]
+    fn simultaneous_follow_the_leader() {
+        let agents = vec![Walker::new(1, 0, 1, 0), Walker::new(0, 0, 1, 0)];
+        let mut scheduler = Scheduler::new(corridor(), agents)
+            .with_order(Order::Simultaneous)
+            .with_collision(Collision::Wait);
+        scheduler.tick();
+        assert_eq!(positions(&scheduler), [2, 1]);
+    }
+
+    #[test]
+    fn sequential_and_priority() {
+        let agents = || vec![Walker::new(0, 0, 1, 0), Walker::new(1, 0, 1, 0)];
+
+        // The trailing agent goes first and bumps into the leader
+        let mut scheduler = Scheduler::new(corridor(), agents()).with_collision(Collision::Wait);
+        scheduler.tick();
+        assert_eq!(positions(&scheduler), [0, 2]);
+
+        let mut agents = agents();
+        agents[1].priority = 1;
+        let mut scheduler = Scheduler::new(corridor(), agents)
+            .with_order(Order::Priority)
+            .with_collision(Collision::Wait);
+        scheduler.tick();
+        assert_eq!(positions(&scheduler), [1, 2]);
+    }
+
+    #[test]
+    fn hooks() {
+        let moves = Rc::new(RefCell::new(Vec::new()));
+        let agents = vec![Walker::new(0, 0, 1, 0)];
+        let mut scheduler = Scheduler::new(corridor(), agents);
+        let log = moves.clone();
+        scheduler.on_tick(move |tick, _maze, agents| {
+            log.borrow_mut().push((tick.number, agents[0].position().x));
+        });
+        let ticks = scheduler.run_until(10, |_maze, agents| agents[0].position().x == 4);
+        assert_eq!(ticks, Some(4));
+        assert_eq!(*moves.borrow(), [(1, 1), (2, 2), (3, 3), (4, 4)]);
+    }
+
+    #[test]
+    fn guided_visitor() {
+        let maze = corridor();
+        // The visitor keeps its own copy of the maze
+        let visitor = Visitor::new(&maze.clone().make_shareable(), Coordinate::new(0, 0));
+        let agent = Guided::new(visitor, |visitor: &Visitor, maze: &Maze| {
+            let next = visitor.coordinate_in_direction(Direction::E)?;
+            (maze.get(next) == Some('.')).then_some(Direction::E)
+        });
+        let mut scheduler = Scheduler::new(maze, vec![agent]);
+        scheduler.maze_mut().upsert(Coordinate::new(3, 0), '#');
+        scheduler.run(10);
+        // The policy sees the wall added to the scheduler's maze, which the visitor's copy does not have
+        assert_eq!(scheduler.agents()[0].position(), Coordinate::new(2, 0));
+        assert_eq!(
+            scheduler.agents()[0].visitor().peek(Direction::E),
+            Some('.')
+        );
+        assert_eq!(scheduler.agents()[0].visitor().path().len(), 3);
+    }
+
+    #[test]
+    fn without_maze() {
+        let agents = vec![Walker::new(0, 0, 1, 0)];
+        let scheduler = Scheduler::without_maze(agents);
+        assert_eq!(scheduler.maze().bounds(), None);
+    }
+}
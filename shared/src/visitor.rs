@@ -1,16 +1,14 @@
 #![allow(clippy::missing_panics_doc)] // TODO: fix various .expects()
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
 };
 
-use itertools::Itertools;
-
 use crate::{
     Coordinate,
     Direction::{self, E, N, NE, NW, S, SE, SW, W},
-    Maze,
+    DirectionSet, Maze,
 };
 
 #[derive(Clone)]
@@ -18,7 +16,7 @@ pub struct Visitor {
     maze: Arc<Mutex<Maze>>,
     coordinate: Coordinate,
     path: Vec<(Coordinate, Direction)>,
-    visited: HashSet<(Coordinate, Direction)>,
+    visited: HashMap<Coordinate, DirectionSet>,
     has_looped: bool,
     pockets: Vec<char>,
 }
@@ -28,10 +26,10 @@ impl Visitor {
     pub fn new(maze: &Arc<Mutex<Maze>>, coordinate: Coordinate) -> Self {
         let maze = maze.clone();
         let mut path = Vec::new();
-        let mut visited = HashSet::new();
+        let mut visited = HashMap::new();
         let has_looped = false;
         path.push((coordinate, N));
-        visited.insert((coordinate, N));
+        visited.insert(coordinate, DirectionSet::from(N));
         let pockets = Vec::new();
         Self {
             maze,
@@ -68,46 +66,15 @@ impl Visitor {
         self.maze.clone()
     }
 
+    /// Returns the neighbouring coordinate in the given direction.
+    ///
+    /// Coordinates may be negative; `None` is only returned if the neighbour would overflow an `isize`.
     #[must_use]
     pub fn coordinate_in_direction(&self, direction: Direction) -> Option<Coordinate> {
-        let x;
-        let y;
-        match direction {
-            Direction::NW => {
-                x = self.coordinate.x.checked_sub(1)?;
-                y = self.coordinate.y.checked_sub(1)?;
-            }
-            Direction::N => {
-                x = self.coordinate.x;
-                y = self.coordinate.y.checked_sub(1)?;
-            }
-            Direction::NE => {
-                x = self.coordinate.x.checked_add(1)?;
-                y = self.coordinate.y.checked_sub(1)?;
-            }
-            Direction::W => {
-                x = self.coordinate.x.checked_sub(1)?;
-                y = self.coordinate.y;
-            }
-            Direction::E => {
-                x = self.coordinate.x.checked_add(1)?;
-                y = self.coordinate.y;
-            }
-            Direction::SW => {
-                x = self.coordinate.x.checked_sub(1)?;
-                y = self.coordinate.y.checked_add(1)?;
-            }
-            Direction::S => {
-                x = self.coordinate.x;
-                y = self.coordinate.y.checked_add(1)?;
-            }
-            Direction::SE => {
-                x = self.coordinate.x.checked_add(1)?;
-                y = self.coordinate.y.checked_add(1)?;
-            }
-        }
-        let coordinate = Coordinate::new(x, y);
-        Some(coordinate)
+        let displacement = direction.to_displacement();
+        let x = self.coordinate.x.checked_add(displacement.x)?;
+        let y = self.coordinate.y.checked_add(displacement.y)?;
+        Some(Coordinate::new(x, y))
     }
 
     #[must_use]
@@ -151,7 +118,11 @@ impl Visitor {
         self.coordinate.y = coordinate.y;
         self.path.push((coordinate, direction));
 
-        let unique = self.visited.insert((coordinate, direction));
+        let unique = self
+            .visited
+            .entry(coordinate)
+            .or_default()
+            .insert(direction);
         if !unique {
             self.has_looped = true;
         }
@@ -214,11 +185,7 @@ impl Visitor {
 
     #[must_use]
     pub fn visited_coordinates(&self) -> Vec<Coordinate> {
-        self.visited
-            .iter()
-            .map(|(coordinate, _direction)| *coordinate)
-            .unique()
-            .collect()
+        self.visited.keys().copied().collect()
     }
 
     #[must_use]
@@ -310,4 +277,55 @@ pub(crate) mod unit {
         ];
         assert_eq!(coordinates_nwes, expected);
     }
+
+    #[test]
+    fn negative_region() {
+        let maze: Arc<Mutex<Maze>> = Maze::parse_at(NUMPAD_MAZE_STR, Coordinate::new(-1, -1))
+            .expect("Unable to parse maze")
+            .make_shareable();
+        let visitor = Visitor::new(&maze, Coordinate::new(0, 0));
+        assert_eq!(visitor.get(), Some('5'));
+        assert_eq!(
+            visitor.surroundings_nwes(),
+            [Some('2'), Some('4'), Some('6'), Some('8')]
+        );
+
+        let mut visitor = Visitor::new(&maze, Coordinate::new(-1, -1));
+        assert_eq!(visitor.get(), Some('1'));
+        assert_eq!(visitor.peek(NW), None);
+        assert_eq!(
+            visitor.coordinate_in_direction(NW),
+            Some(Coordinate::new(-2, -2))
+        );
+        assert_eq!(visitor.step(SE), Some('5'));
+        assert_eq!(visitor.step(SE), Some('9'));
+        assert_eq!(visitor.position(), Coordinate::new(1, 1));
+    }
+
+    #[test]
+    fn flood_negative_region() {
+        let maze: Arc<Mutex<Maze>> = Maze::parse_at("aab\nabb", Coordinate::new(-3, -5))
+            .expect("Unable to parse maze")
+            .make_shareable();
+        let visitor = Visitor::new(&maze, Coordinate::new(-3, -4));
+        let flood = visitor.flood_nwes().expect("Unable to flood");
+        let expected = HashSet::from([
+            Coordinate::new(-3, -5),
+            Coordinate::new(-2, -5),
+            Coordinate::new(-3, -4),
+        ]);
+        assert_eq!(flood, expected);
+    }
+
+    #[test]
+    fn overflow() {
+        let maze = Maze::default().make_shareable();
+        let visitor = Visitor::new(&maze, Coordinate::new(isize::MIN, isize::MAX));
+        assert_eq!(visitor.coordinate_in_direction(W), None);
+        assert_eq!(visitor.coordinate_in_direction(S), None);
+        assert_eq!(
+            visitor.coordinate_in_direction(NE),
+            Some(Coordinate::new(isize::MIN + 1, isize::MAX - 1))
+        );
+    }
 }